@@ -19,6 +19,7 @@ export interface McpConfig {
   docker_image?: string;
   binary_path?: string;
   http_url?: string;
+  http_transport?: "Auto" | "StreamableHttp" | "Sse";
   command?: string;
   args: string[];
   env_vars: EnvVar[];
@@ -36,6 +37,34 @@ export interface ProjectMcpBinding {
   mcp_id: string;
   enabled: boolean;
   overrides: EnvVar[];
+  tool_settings: ToolSettings;
+}
+
+export interface ToolSettings {
+  allowed_tools: string[];
+  denied_tools: string[];
+  overrides: Record<string, ToolOverride>;
+  pinned_args: Record<string, unknown>;
+  default_args: Record<string, unknown>;
+  prefix?: string;
+  separator?: string;
+}
+
+export interface ToolName {
+  scope: string;
+  exposed_name: string;
+  binding_id: string;
+  mcp_id: string;
+  tool_name: string;
+  collision?: string;
+  updated_at: string;
+}
+
+export interface ToolOverride {
+  name?: string;
+  description?: string;
+  description_suffix?: string;
+  title?: string;
 }
 
 export interface Secret {
@@ -44,6 +73,30 @@ export interface Secret {
   created_at: string;
 }
 
+export interface RouterLog {
+  id: string;
+  timestamp: string;
+  project_id: string;
+  mcp_id: string;
+  tool_name: string;
+  status: "success" | "error";
+  duration_ms: number;
+  error?: string;
+  args_size?: number;
+  result_size?: number;
+}
+
+export interface RouterLogFilter {
+  project_id?: string;
+  mcp_id?: string;
+  tool_name?: string;
+  status?: "success" | "error";
+  since?: string;
+  until?: string;
+  limit?: number;
+  offset?: number;
+}
+
 export const api = {
   async createProject(name: string, path: string): Promise<Project> {
     return await invoke("create_project", { name, path });
@@ -97,6 +150,62 @@ export const api = {
     return await invoke("update_binding", { binding });
   },
 
+  async setBindingToolFilter(
+    bindingId: string,
+    allowedTools: string[],
+    deniedTools: string[]
+  ): Promise<ProjectMcpBinding> {
+    return await invoke("set_binding_tool_filter", {
+      bindingId,
+      allowedTools,
+      deniedTools,
+    });
+  },
+
+  async setBindingToolOverride(
+    bindingId: string,
+    toolName: string,
+    toolOverride: ToolOverride | null
+  ): Promise<ProjectMcpBinding> {
+    return await invoke("set_binding_tool_override", {
+      bindingId,
+      toolName,
+      toolOverride,
+    });
+  },
+
+  async setBindingToolArgs(
+    bindingId: string,
+    pinnedArgs: Record<string, unknown>,
+    defaultArgs: Record<string, unknown>
+  ): Promise<ProjectMcpBinding> {
+    return await invoke("set_binding_tool_args", {
+      bindingId,
+      pinnedArgs,
+      defaultArgs,
+    });
+  },
+
+  async setBindingNaming(
+    bindingId: string,
+    prefix: string | null,
+    separator: string | null
+  ): Promise<ProjectMcpBinding> {
+    return await invoke("set_binding_naming", { bindingId, prefix, separator });
+  },
+
+  async listToolNames(projectId: string): Promise<ToolName[]> {
+    return await invoke("list_tool_names", { projectId });
+  },
+
+  async listRouterLogs(filter: RouterLogFilter): Promise<RouterLog[]> {
+    return await invoke("list_router_logs", { filter });
+  },
+
+  async clearRouterLogs(projectId?: string): Promise<number> {
+    return await invoke("clear_router_logs", { projectId });
+  },
+
   async saveSecret(key: string, value: string): Promise<Secret> {
     return await invoke("save_secret", { key, value });
   },
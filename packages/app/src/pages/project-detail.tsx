@@ -1,6 +1,6 @@
 import { useEffect, useState } from "react";
 import { useParams, useNavigate } from "react-router-dom";
-import { api, ProjectMcpBinding, Mcp, EnvVar } from "../api";
+import { api, ProjectMcpBinding, Mcp, EnvVar, ToolName } from "../api";
 
 function ProjectDetail() {
   const { id } = useParams<{ id: string }>();
@@ -11,11 +11,15 @@ function ProjectDetail() {
   const [selectedMcpId, setSelectedMcpId] = useState("");
   const [editingBinding, setEditingBinding] = useState<ProjectMcpBinding | null>(null);
   const [overrides, setOverrides] = useState<EnvVar[]>([]);
+  const [prefix, setPrefix] = useState("");
+  const [separator, setSeparator] = useState("");
+  const [toolNames, setToolNames] = useState<ToolName[]>([]);
 
   useEffect(() => {
     if (id) {
       loadBindings();
       loadMcps();
+      loadToolNames();
     }
   }, [id]);
 
@@ -30,6 +34,14 @@ function ProjectDetail() {
     setAllMcps(data);
   };
 
+  const loadToolNames = async () => {
+    if (!id) return;
+    const data = await api.listToolNames(id);
+    setToolNames(data);
+  };
+
+  const collisions = toolNames.filter((name) => name.collision);
+
   const handleActivate = async () => {
     if (!id || !selectedMcpId) return;
     try {
@@ -55,12 +67,15 @@ function ProjectDetail() {
   const handleEditOverrides = (binding: ProjectMcpBinding) => {
     setEditingBinding(binding);
     setOverrides([...binding.overrides]);
+    setPrefix(binding.tool_settings.prefix || "");
+    setSeparator(binding.tool_settings.separator || "");
   };
 
   const handleSaveOverrides = async () => {
     if (!editingBinding) return;
     try {
-      await api.updateBinding({ ...editingBinding, overrides });
+      const updated = await api.setBindingNaming(editingBinding.id, prefix || null, separator || null);
+      await api.updateBinding({ ...updated, overrides });
       setEditingBinding(null);
       setOverrides([]);
       loadBindings();
@@ -95,6 +110,26 @@ function ProjectDetail() {
         </div>
       </div>
 
+      {collisions.length > 0 && (
+        <div className="card">
+          <h3>Tool name collisions</h3>
+          <p>
+            These tools wanted the same name, so the router added a suffix to keep them apart. Set a prefix on
+            one of the MCPs to get readable names back.
+          </p>
+          {collisions.map((name) => {
+            const mcp = allMcps.find((m) => m.id === name.mcp_id);
+            return (
+              <div key={`${name.scope}/${name.exposed_name}`} style={{ marginTop: 5 }}>
+                <span className="badge warning">{name.collision}</span> {mcp?.name || "Unknown MCP"} /{" "}
+                {name.tool_name} is exposed as <code>{name.exposed_name}</code>
+                {name.scope === "*" && " when no project is selected"}
+              </div>
+            );
+          })}
+        </div>
+      )}
+
       <div>
         {bindings.map((binding) => {
           const mcp = allMcps.find((m) => m.id === binding.mcp_id);
@@ -154,6 +189,18 @@ function ProjectDetail() {
               <h2>Edit Overrides</h2>
               <button className="close-btn" onClick={() => setEditingBinding(null)}>×</button>
             </div>
+            <div className="form-group">
+              <label>Tool name prefix</label>
+              <input
+                value={prefix}
+                onChange={(e) => setPrefix(e.target.value)}
+                placeholder="Defaults to the MCP name"
+              />
+            </div>
+            <div className="form-group">
+              <label>Separator</label>
+              <input value={separator} onChange={(e) => setSeparator(e.target.value)} placeholder="__" />
+            </div>
             <div style={{ marginBottom: 20 }}>
               {overrides.map((override, index) => (
                 <div key={index} style={{ marginBottom: 10, padding: 10, backgroundColor: "#1a1a1a", borderRadius: 5 }}>
@@ -1,7 +1,14 @@
 use crate::models::*;
 use crate::storage::Storage;
+use serde_json::Value;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
+/// Leaves room for tool names within the 64 characters clients accept
+const MAX_PREFIX_LEN: usize = 32;
+
+const MAX_SEPARATOR_LEN: usize = 3;
+
 pub struct BindingManager<'a> {
     storage: &'a Storage,
 }
@@ -18,6 +25,7 @@ impl<'a> BindingManager<'a> {
             mcp_id,
             enabled: true,
             overrides,
+            tool_settings: ToolSettings::default(),
         };
 
         self.storage
@@ -33,9 +41,187 @@ impl<'a> BindingManager<'a> {
             .map_err(|e| e.to_string())
     }
 
+    /// Saves `enabled` and the env overrides; tool settings are changed through
+    /// their own commands, which validate them
     pub fn update_binding(&self, binding: ProjectMcpBinding) -> Result<(), String> {
         self.storage
             .update_binding(&binding)
             .map_err(|e| e.to_string())
     }
+
+    /// Replace the glob patterns selecting which of the server's tools the project sees
+    pub fn set_tool_filter(
+        &self,
+        binding_id: String,
+        allowed_tools: Vec<String>,
+        denied_tools: Vec<String>,
+    ) -> Result<ProjectMcpBinding, String> {
+        let mut binding = self
+            .storage
+            .get_binding(&binding_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Binding not found: {}", binding_id))?;
+
+        binding.tool_settings.allowed_tools = clean_patterns(allowed_tools);
+        binding.tool_settings.denied_tools = clean_patterns(denied_tools);
+
+        self.storage
+            .update_tool_settings(&binding.id, &binding.tool_settings)
+            .map_err(|e| e.to_string())?;
+
+        Ok(binding)
+    }
+
+    /// Set how one of the server's tools is presented, or remove its override with `None`
+    pub fn set_tool_override(
+        &self,
+        binding_id: String,
+        tool_name: String,
+        tool_override: Option<ToolOverride>,
+    ) -> Result<ProjectMcpBinding, String> {
+        let mut binding = self
+            .storage
+            .get_binding(&binding_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Binding not found: {}", binding_id))?;
+
+        let overrides = &mut binding.tool_settings.overrides;
+        match tool_override.map(clean_override) {
+            Some(tool_override) => {
+                if let Some(alias) = &tool_override.name {
+                    validate_alias(alias)?;
+                    if let Some((other, _)) = overrides
+                        .iter()
+                        .find(|(name, o)| **name != tool_name && o.name.as_ref() == Some(alias))
+                    {
+                        return Err(format!("'{}' is already the name of tool '{}'", alias, other));
+                    }
+                }
+                overrides.insert(tool_name, tool_override);
+            }
+            None => {
+                overrides.remove(&tool_name);
+            }
+        }
+
+        self.storage
+            .update_tool_settings(&binding.id, &binding.tool_settings)
+            .map_err(|e| e.to_string())?;
+
+        Ok(binding)
+    }
+
+    /// Replace the arguments the router injects into this binding's tool calls
+    pub fn set_tool_args(
+        &self,
+        binding_id: String,
+        pinned_args: BTreeMap<String, Value>,
+        default_args: BTreeMap<String, Value>,
+    ) -> Result<ProjectMcpBinding, String> {
+        let mut binding = self
+            .storage
+            .get_binding(&binding_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Binding not found: {}", binding_id))?;
+
+        if let Some(name) = pinned_args.keys().chain(default_args.keys()).find(|k| k.trim().is_empty()) {
+            return Err(format!("Invalid argument name '{}'", name));
+        }
+        if let Some(name) = pinned_args.keys().find(|k| default_args.contains_key(*k)) {
+            return Err(format!("Argument '{}' can't be both pinned and defaulted", name));
+        }
+
+        binding.tool_settings.pinned_args = pinned_args;
+        binding.tool_settings.default_args = default_args;
+
+        self.storage
+            .update_tool_settings(&binding.id, &binding.tool_settings)
+            .map_err(|e| e.to_string())?;
+
+        Ok(binding)
+    }
+
+    /// Set the prefix and separator of the binding's exposed names; `None`
+    /// restores the MCP name and `__`
+    pub fn set_naming(
+        &self,
+        binding_id: String,
+        prefix: Option<String>,
+        separator: Option<String>,
+    ) -> Result<ProjectMcpBinding, String> {
+        let mut binding = self
+            .storage
+            .get_binding(&binding_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Binding not found: {}", binding_id))?;
+
+        let prefix = prefix.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+        let separator = separator.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+
+        if let Some(prefix) = &prefix {
+            if prefix.len() > MAX_PREFIX_LEN
+                || !prefix.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(format!(
+                    "Invalid prefix '{}': use up to {} letters, digits, '_' or '-'",
+                    prefix, MAX_PREFIX_LEN
+                ));
+            }
+        }
+        if let Some(separator) = &separator {
+            if separator.len() > MAX_SEPARATOR_LEN || !separator.chars().all(|c| c == '_' || c == '-') {
+                return Err(format!(
+                    "Invalid separator '{}': use up to {} '_' or '-' characters",
+                    separator, MAX_SEPARATOR_LEN
+                ));
+            }
+        }
+
+        binding.tool_settings.prefix = prefix;
+        binding.tool_settings.separator = separator;
+
+        self.storage
+            .update_tool_settings(&binding.id, &binding.tool_settings)
+            .map_err(|e| e.to_string())?;
+
+        Ok(binding)
+    }
+
+    /// Tool names the router last exposed for the project, on its own or
+    /// alongside every other project, including collisions it had to resolve
+    pub fn list_tool_names(&self, project_id: String) -> Result<Vec<ToolName>, String> {
+        self.storage
+            .get_project_tool_names(&project_id)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Blank fields mean "keep the server's value"
+fn clean_override(tool_override: ToolOverride) -> ToolOverride {
+    let clean = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    ToolOverride {
+        name: clean(tool_override.name),
+        description: clean(tool_override.description),
+        description_suffix: clean(tool_override.description_suffix),
+        title: clean(tool_override.title),
+    }
+}
+
+/// Tool names must match ^[a-zA-Z0-9_-]{1,64}$ once the server prefix is added
+fn validate_alias(alias: &str) -> Result<(), String> {
+    if alias.len() > 64 || !alias.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!(
+            "Invalid tool name '{}': use up to 64 letters, digits, '_' or '-'",
+            alias
+        ));
+    }
+    Ok(())
+}
+
+fn clean_patterns(patterns: Vec<String>) -> Vec<String> {
+    patterns
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
 }
@@ -2,10 +2,12 @@ use crate::bindings::BindingManager;
 use crate::mcps::McpManager;
 use crate::models::*;
 use crate::projects::ProjectManager;
+use crate::router_logs::RouterLogManager;
 use crate::secrets::SecretManager;
 use crate::storage::Storage;
 use crate::utils;
 use chrono::Utc;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tauri_plugin_clipboard_manager::ClipboardExt;
 use uuid::Uuid;
@@ -108,6 +110,77 @@ pub async fn update_binding(
     manager.update_binding(binding)
 }
 
+#[tauri::command]
+pub async fn set_binding_tool_filter(
+    binding_id: String,
+    allowed_tools: Vec<String>,
+    denied_tools: Vec<String>,
+    storage: tauri::State<'_, Arc<Storage>>,
+) -> Result<ProjectMcpBinding, String> {
+    let manager = BindingManager::new(&storage);
+    manager.set_tool_filter(binding_id, allowed_tools, denied_tools)
+}
+
+#[tauri::command]
+pub async fn set_binding_tool_override(
+    binding_id: String,
+    tool_name: String,
+    tool_override: Option<ToolOverride>,
+    storage: tauri::State<'_, Arc<Storage>>,
+) -> Result<ProjectMcpBinding, String> {
+    let manager = BindingManager::new(&storage);
+    manager.set_tool_override(binding_id, tool_name, tool_override)
+}
+
+#[tauri::command]
+pub async fn set_binding_tool_args(
+    binding_id: String,
+    pinned_args: BTreeMap<String, serde_json::Value>,
+    default_args: BTreeMap<String, serde_json::Value>,
+    storage: tauri::State<'_, Arc<Storage>>,
+) -> Result<ProjectMcpBinding, String> {
+    let manager = BindingManager::new(&storage);
+    manager.set_tool_args(binding_id, pinned_args, default_args)
+}
+
+#[tauri::command]
+pub async fn set_binding_naming(
+    binding_id: String,
+    prefix: Option<String>,
+    separator: Option<String>,
+    storage: tauri::State<'_, Arc<Storage>>,
+) -> Result<ProjectMcpBinding, String> {
+    let manager = BindingManager::new(&storage);
+    manager.set_naming(binding_id, prefix, separator)
+}
+
+#[tauri::command]
+pub async fn list_tool_names(
+    project_id: String,
+    storage: tauri::State<'_, Arc<Storage>>,
+) -> Result<Vec<ToolName>, String> {
+    let manager = BindingManager::new(&storage);
+    manager.list_tool_names(project_id)
+}
+
+#[tauri::command]
+pub async fn list_router_logs(
+    filter: RouterLogFilter,
+    storage: tauri::State<'_, Arc<Storage>>,
+) -> Result<Vec<RouterLog>, String> {
+    let manager = RouterLogManager::new(&storage);
+    manager.list_router_logs(filter)
+}
+
+#[tauri::command]
+pub async fn clear_router_logs(
+    project_id: Option<String>,
+    storage: tauri::State<'_, Arc<Storage>>,
+) -> Result<usize, String> {
+    let manager = RouterLogManager::new(&storage);
+    manager.clear_router_logs(project_id)
+}
+
 #[tauri::command]
 pub async fn save_secret(
     key: String,
@@ -137,29 +210,15 @@ pub async fn list_secrets(storage: tauri::State<'_, Arc<Storage>>) -> Result<Vec
 
 #[tauri::command]
 pub async fn generate_mcp_config(
-    _project_id: String,
+    project_id: String,
     storage: tauri::State<'_, Arc<Storage>>,
 ) -> Result<String, String> {
-    let app_dir = dirs::data_local_dir()
-        .ok_or("Could not find local data directory")?
-        .join("com.mcp.manager");
-
-    #[cfg(target_os = "windows")]
-    let mcp_stdio_path = app_dir.join("mcp-stdio.exe");
-
-    #[cfg(not(target_os = "windows"))]
-    let mcp_stdio_path = app_dir.join("mcp-stdio");
-
-    let config = serde_json::json!({
-        "mcpServers": {
-            "mcp-manager": {
-                "command": mcp_stdio_path.to_str().unwrap(),
-                "args": []
-            }
-        }
-    });
-
-    Ok(serde_json::to_string_pretty(&config).unwrap())
+    let manager = ProjectManager::new(&storage);
+    if !manager.list_projects()?.iter().any(|p| p.id == project_id) {
+        return Err(format!("Project not found: {}", project_id));
+    }
+
+    utils::generate_project_mcp_config(&project_id)
 }
 
 #[tauri::command]
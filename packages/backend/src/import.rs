@@ -4,11 +4,20 @@ use std::collections::HashMap;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct McpServerConfig {
+    #[serde(default)]
     pub command: String,
     #[serde(default)]
     pub args: Vec<String>,
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Remote servers are given as a URL instead of a command
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Remote transport: "sse", "http" or "streamable-http"
+    #[serde(default, rename = "type")]
+    pub transport: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,7 +42,9 @@ pub fn parse_mcp_json(json_str: &str) -> Result<Vec<(String, McpConfig)>, String
 }
 
 fn detect_mcp_type(config: &McpServerConfig) -> McpType {
-    if config.command.starts_with("docker") || config.command.contains("docker") {
+    if config.url.is_some() {
+        McpType::Http
+    } else if config.command.starts_with("docker") || config.command.contains("docker") {
         McpType::Docker
     } else if config.command.starts_with("http") || config.command.starts_with("https") {
         McpType::Http
@@ -43,9 +54,16 @@ fn detect_mcp_type(config: &McpServerConfig) -> McpType {
 }
 
 fn convert_to_mcp_config(config: McpServerConfig, mcp_type: &McpType) -> McpConfig {
+    // HTTP headers are stored as header_<Name> env vars
+    let headers = config
+        .headers
+        .into_iter()
+        .map(|(name, value)| (format!("header_{}", name), value));
+
     let env_vars: Vec<EnvVar> = config
         .env
         .into_iter()
+        .chain(headers)
         .map(|(key, value)| {
             let is_secret = is_likely_secret(&key);
             EnvVar {
@@ -56,12 +74,23 @@ fn convert_to_mcp_config(config: McpServerConfig, mcp_type: &McpType) -> McpConf
         })
         .collect();
 
+    let http_transport = match config.transport.as_deref() {
+        Some("sse") => HttpTransport::Sse,
+        Some("http") | Some("streamable-http") | Some("streamableHttp") => {
+            HttpTransport::StreamableHttp
+        }
+        _ => HttpTransport::Auto,
+    };
+
     let (docker_image, binary_path, http_url) = match mcp_type {
         McpType::Docker => {
             let image = config.args.first().cloned();
             (image, None, None)
         }
-        McpType::Http => (None, None, Some(config.command.clone())),
+        McpType::Http => {
+            let url = config.url.clone().unwrap_or_else(|| config.command.clone());
+            (None, None, Some(url))
+        }
         McpType::Binary => (None, Some(config.command.clone()), None),
     };
 
@@ -69,7 +98,8 @@ fn convert_to_mcp_config(config: McpServerConfig, mcp_type: &McpType) -> McpConf
         docker_image,
         binary_path,
         http_url,
-        command: Some(config.command),
+        http_transport,
+        command: Some(config.command).filter(|c| !c.is_empty()),
         args: config.args,
         env_vars,
     }
@@ -5,6 +5,7 @@ mod import;
 mod mcps;
 mod models;
 mod projects;
+mod router_logs;
 mod secrets;
 mod storage;
 mod utils;
@@ -48,6 +49,13 @@ pub fn run() {
             commands::activate_mcp,
             commands::list_bindings,
             commands::update_binding,
+            commands::set_binding_tool_filter,
+            commands::set_binding_tool_override,
+            commands::set_binding_tool_args,
+            commands::set_binding_naming,
+            commands::list_tool_names,
+            commands::list_router_logs,
+            commands::clear_router_logs,
             commands::save_secret,
             commands::list_secrets,
             commands::generate_mcp_config,
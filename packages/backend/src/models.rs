@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
@@ -29,11 +30,24 @@ pub struct McpConfig {
     pub docker_image: Option<String>,
     pub binary_path: Option<String>,
     pub http_url: Option<String>,
+    #[serde(default)]
+    pub http_transport: HttpTransport,
     pub command: Option<String>,
     pub args: Vec<String>,
     pub env_vars: Vec<EnvVar>,
 }
 
+/// Wire protocol spoken by an `McpType::Http` server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HttpTransport {
+    /// Streamable HTTP, falling back to HTTP+SSE if the server rejects it
+    #[default]
+    Auto,
+    StreamableHttp,
+    /// The legacy HTTP+SSE transport from protocol version 2024-11-05
+    Sse,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnvVar {
     pub key: String,
@@ -48,6 +62,65 @@ pub struct ProjectMcpBinding {
     pub mcp_id: String,
     pub enabled: bool,
     pub overrides: Vec<EnvVar>,
+    #[serde(default)]
+    pub tool_settings: ToolSettings,
+}
+
+/// How a binding exposes the server's tools
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolSettings {
+    /// Glob patterns (`*`, `?`); when set, only matching tools are exposed
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// Glob patterns for tools that are never exposed, even if allowed
+    #[serde(default)]
+    pub denied_tools: Vec<String>,
+    /// Per-tool changes, keyed by the server's tool name
+    #[serde(default)]
+    pub overrides: BTreeMap<String, ToolOverride>,
+    /// Arguments always sent with these values; hidden from the client
+    #[serde(default)]
+    pub pinned_args: BTreeMap<String, serde_json::Value>,
+    /// Arguments filled in when the client leaves them out
+    #[serde(default)]
+    pub default_args: BTreeMap<String, serde_json::Value>,
+    /// Replaces the MCP name in exposed tool and prompt names
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// Between the prefix and the tool name; `__` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+}
+
+/// The exposed name the router gave a downstream tool, persisted so calls can be mapped back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolName {
+    /// Project id, or `*` when the router serves every project
+    pub scope: String,
+    pub exposed_name: String,
+    pub binding_id: String,
+    pub mcp_id: String,
+    /// The server's name for the tool
+    pub tool_name: String,
+    /// The name this tool would have had, when another tool wanted it too
+    pub collision: Option<String>,
+    pub updated_at: String,
+}
+
+/// Changes to how one tool is presented to the AI client
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolOverride {
+    /// Exposed instead of the server's tool name, still under the server prefix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Replaces the server's description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Appended to the description, after any replacement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_suffix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,3 +129,42 @@ pub struct Secret {
     pub key: String,
     pub created_at: String,
 }
+
+/// One routed `tools/call`, as recorded by the router
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouterLog {
+    pub id: String,
+    pub timestamp: String,
+    pub project_id: String,
+    pub mcp_id: String,
+    pub tool_name: String,
+    /// "success" or "error"
+    pub status: String,
+    pub duration_ms: i64,
+    pub error: Option<String>,
+    pub args_size: Option<i64>,
+    pub result_size: Option<i64>,
+}
+
+/// Narrows a router log query; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouterLogFilter {
+    #[serde(default)]
+    pub project_id: Option<String>,
+    #[serde(default)]
+    pub mcp_id: Option<String>,
+    /// Matches tool names containing this text
+    #[serde(default)]
+    pub tool_name: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    /// RFC 3339 timestamps bounding the entries returned
+    #[serde(default)]
+    pub since: Option<String>,
+    #[serde(default)]
+    pub until: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: Option<u32>,
+}
@@ -0,0 +1,32 @@
+use crate::models::*;
+use crate::storage::Storage;
+
+/// Entries returned when the filter sets no limit
+const DEFAULT_LIMIT: u32 = 100;
+
+/// Upper bound on entries returned by one query
+const MAX_LIMIT: u32 = 1000;
+
+pub struct RouterLogManager<'a> {
+    storage: &'a Storage,
+}
+
+impl<'a> RouterLogManager<'a> {
+    pub fn new(storage: &'a Storage) -> Self {
+        RouterLogManager { storage }
+    }
+
+    pub fn list_router_logs(&self, filter: RouterLogFilter) -> Result<Vec<RouterLog>, String> {
+        let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        let offset = filter.offset.unwrap_or(0);
+        self.storage
+            .get_router_logs(&filter, limit, offset)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn clear_router_logs(&self, project_id: Option<String>) -> Result<usize, String> {
+        self.storage
+            .delete_router_logs(project_id.as_deref())
+            .map_err(|e| e.to_string())
+    }
+}
@@ -1,7 +1,10 @@
 use crate::models::*;
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, params_from_iter, Connection, Result};
 use std::sync::Mutex;
 
+/// Scope of the tool names the router exposes when no project is selected
+const ALL_PROJECTS_SCOPE: &str = "*";
+
 pub struct Storage {
     conn: Mutex<Connection>,
 }
@@ -47,11 +50,13 @@ impl Storage {
                 mcp_id TEXT NOT NULL,
                 enabled INTEGER NOT NULL,
                 overrides TEXT NOT NULL,
+                tool_settings TEXT NOT NULL DEFAULT '{}',
                 FOREIGN KEY(project_id) REFERENCES projects(id),
                 FOREIGN KEY(mcp_id) REFERENCES mcps(id)
             )",
             [],
         )?;
+        add_column_if_missing(&conn, "project_mcp_bindings", "tool_settings", "TEXT NOT NULL DEFAULT '{}'")?;
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS secrets (
@@ -63,6 +68,43 @@ impl Storage {
             [],
         )?;
 
+        // Exposed tool names assigned by the router
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tool_names (
+                scope TEXT NOT NULL,
+                exposed_name TEXT NOT NULL,
+                binding_id TEXT NOT NULL,
+                mcp_id TEXT NOT NULL,
+                tool_name TEXT NOT NULL,
+                collision TEXT,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY(scope, exposed_name)
+            )",
+            [],
+        )?;
+
+        // Written by the router; created here too so the app can query it first
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS router_logs (
+                id TEXT PRIMARY KEY,
+                timestamp TEXT NOT NULL,
+                project_id TEXT NOT NULL,
+                mcp_id TEXT NOT NULL,
+                tool_name TEXT NOT NULL,
+                status TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                error TEXT,
+                args_size INTEGER,
+                result_size INTEGER
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_router_logs_timestamp ON router_logs(timestamp)",
+            [],
+        )?;
+
         Ok(())
     }
 
@@ -158,30 +200,38 @@ impl Storage {
     pub fn insert_binding(&self, binding: &ProjectMcpBinding) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         let overrides_json = serde_json::to_string(&binding.overrides).unwrap();
+        let tool_settings_json = serde_json::to_string(&binding.tool_settings).unwrap();
         conn.execute(
-            "INSERT INTO project_mcp_bindings (id, project_id, mcp_id, enabled, overrides) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![binding.id, binding.project_id, binding.mcp_id, binding.enabled as i32, overrides_json],
+            "INSERT INTO project_mcp_bindings (id, project_id, mcp_id, enabled, overrides, tool_settings) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![binding.id, binding.project_id, binding.mcp_id, binding.enabled as i32, overrides_json, tool_settings_json],
         )?;
         Ok(())
     }
 
     pub fn get_bindings_by_project(&self, project_id: &str) -> Result<Vec<ProjectMcpBinding>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT id, project_id, mcp_id, enabled, overrides FROM project_mcp_bindings WHERE project_id = ?1")?;
-        let bindings = stmt.query_map(params![project_id], |row| {
-            let overrides_json: String = row.get(4)?;
-            let overrides: Vec<EnvVar> = serde_json::from_str(&overrides_json).unwrap();
+        let mut stmt = conn.prepare("SELECT id, project_id, mcp_id, enabled, overrides, tool_settings FROM project_mcp_bindings WHERE project_id = ?1")?;
+        let bindings = stmt.query_map(params![project_id], binding_from_row)?;
+        bindings.collect()
+    }
+
+    /// Reads a binding whose tool settings are about to be changed. Unlike the
+    /// lists, this fails on unreadable settings, so saving can't replace them
+    /// with the defaults.
+    pub fn get_binding(&self, id: &str) -> Result<Option<ProjectMcpBinding>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, project_id, mcp_id, enabled, overrides, tool_settings FROM project_mcp_bindings WHERE id = ?1")?;
+        let mut bindings = stmt.query_map(params![id], |row| {
             Ok(ProjectMcpBinding {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                mcp_id: row.get(2)?,
-                enabled: row.get::<_, i32>(3)? != 0,
-                overrides,
+                tool_settings: tool_settings_from_row(row)?,
+                ..binding_from_row(row)?
             })
         })?;
-        bindings.collect()
+        bindings.next().transpose()
     }
 
+    /// Updates whether the binding is enabled and its env overrides. Tool
+    /// settings are only written by `update_tool_settings`.
     pub fn update_binding(&self, binding: &ProjectMcpBinding) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         let overrides_json = serde_json::to_string(&binding.overrides).unwrap();
@@ -192,6 +242,16 @@ impl Storage {
         Ok(())
     }
 
+    pub fn update_tool_settings(&self, binding_id: &str, tool_settings: &ToolSettings) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let tool_settings_json = serde_json::to_string(tool_settings).unwrap();
+        conn.execute(
+            "UPDATE project_mcp_bindings SET tool_settings = ?1 WHERE id = ?2",
+            params![tool_settings_json, binding_id],
+        )?;
+        Ok(())
+    }
+
     pub fn insert_secret(&self, id: &str, key: &str, encrypted_value: &str, created_at: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
@@ -224,4 +284,120 @@ impl Storage {
             Ok(None)
         }
     }
+
+    /// Router log entries matching the filter, newest first
+    pub fn get_router_logs(&self, filter: &RouterLogFilter, limit: u32, offset: u32) -> Result<Vec<RouterLog>> {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        let mut bind = |condition: &str, value: Option<&String>| {
+            if let Some(value) = value {
+                values.push(value.clone());
+                conditions.push(condition.replace('?', &format!("?{}", values.len())));
+            }
+        };
+        bind("project_id = ?", filter.project_id.as_ref());
+        bind("mcp_id = ?", filter.mcp_id.as_ref());
+        bind("instr(tool_name, ?) > 0", filter.tool_name.as_ref());
+        bind("status = ?", filter.status.as_ref());
+        bind("timestamp >= ?", filter.since.as_ref());
+        bind("timestamp <= ?", filter.until.as_ref());
+
+        let mut sql = "SELECT id, timestamp, project_id, mcp_id, tool_name, status, duration_ms, error, args_size, result_size FROM router_logs".to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(&format!(" ORDER BY timestamp DESC LIMIT {} OFFSET {}", limit, offset));
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+        let logs = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(RouterLog {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                project_id: row.get(2)?,
+                mcp_id: row.get(3)?,
+                tool_name: row.get(4)?,
+                status: row.get(5)?,
+                duration_ms: row.get(6)?,
+                error: row.get(7)?,
+                args_size: row.get(8)?,
+                result_size: row.get(9)?,
+            })
+        })?;
+        logs.collect()
+    }
+
+    /// Delete router log entries, for one project or all of them
+    pub fn delete_router_logs(&self, project_id: Option<&str>) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM router_logs WHERE ?1 IS NULL OR project_id = ?1",
+            params![project_id],
+        )
+    }
+
+    /// Names exposed for the project's bindings, when the router serves the
+    /// project and when it serves every project
+    pub fn get_project_tool_names(&self, project_id: &str) -> Result<Vec<ToolName>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT scope, exposed_name, binding_id, mcp_id, tool_name, collision, updated_at
+             FROM tool_names
+             WHERE scope = ?1 OR (scope = ?2 AND binding_id IN (
+                SELECT id FROM project_mcp_bindings WHERE project_id = ?1
+             ))
+             ORDER BY scope, exposed_name",
+        )?;
+        let names = stmt.query_map(params![project_id, ALL_PROJECTS_SCOPE], |row| {
+            Ok(ToolName {
+                scope: row.get(0)?,
+                exposed_name: row.get(1)?,
+                binding_id: row.get(2)?,
+                mcp_id: row.get(3)?,
+                tool_name: row.get(4)?,
+                collision: row.get(5)?,
+                updated_at: row.get(6)?,
+            })
+        })?;
+        names.collect()
+    }
+}
+
+/// Reads a binding for listing. Tool settings that aren't valid JSON are read
+/// as the defaults, so one bad row doesn't hide the project's other bindings.
+fn binding_from_row(row: &rusqlite::Row) -> Result<ProjectMcpBinding> {
+    let overrides_json: String = row.get(4)?;
+    let overrides: Vec<EnvVar> = serde_json::from_str(&overrides_json).unwrap();
+    let id: String = row.get(0)?;
+    let tool_settings = tool_settings_from_row(row).unwrap_or_else(|e| {
+        eprintln!("[Storage] Unreadable tool settings for binding {}: {}", id, e);
+        ToolSettings::default()
+    });
+    Ok(ProjectMcpBinding {
+        id,
+        project_id: row.get(1)?,
+        mcp_id: row.get(2)?,
+        enabled: row.get::<_, i32>(3)? != 0,
+        overrides,
+        tool_settings,
+    })
+}
+
+fn tool_settings_from_row(row: &rusqlite::Row) -> Result<ToolSettings> {
+    let tool_settings_json: String = row.get(5)?;
+    serde_json::from_str(&tool_settings_json).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+/// Adds a column to a table created by an older version
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>>>()?;
+
+    if !columns.iter().any(|c| c == column) {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
 }
@@ -49,13 +49,22 @@ pub fn get_mcp_stdio_path() -> Result<String, String> {
 
 /// Genera la configuración JSON para MCP Toolkit
 pub fn generate_mcp_config() -> Result<String, String> {
+    build_mcp_config(Vec::new())
+}
+
+/// Genera la configuración JSON limitada a un proyecto (`--project <id>`)
+pub fn generate_project_mcp_config(project_id: &str) -> Result<String, String> {
+    build_mcp_config(vec!["--project".to_string(), project_id.to_string()])
+}
+
+fn build_mcp_config(args: Vec<String>) -> Result<String, String> {
     let command_path = get_mcp_stdio_path()?;
 
     let config = serde_json::json!({
         "mcpServers": {
             "mcp-toolkit": {
                 "command": command_path,
-                "args": []
+                "args": args
             }
         }
     });
@@ -84,4 +93,12 @@ mod tests {
         assert!(config_str.contains("mcp-toolkit"));
         assert!(config_str.contains("command"));
     }
+
+    #[test]
+    fn test_generate_project_mcp_config() {
+        let config = generate_project_mcp_config("project-123").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&config).unwrap();
+        let args = &value["mcpServers"]["mcp-toolkit"]["args"];
+        assert_eq!(args, &serde_json::json!(["--project", "project-123"]));
+    }
 }
@@ -0,0 +1,104 @@
+use crate::mcp_client::McpClient;
+use crate::mcp_protocol::*;
+use crate::models::*;
+use crate::router::Router;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Per-server deadline when aggregating list results from every bound server
+pub const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Upper bound on pages fetched from one server, in case its cursor never ends
+const MAX_PAGES: usize = 100;
+
+/// Items returned by each server, plus the servers that could not be queried
+pub type FanOutResult = (Vec<(Mcp, ProjectMcpBinding, Vec<Value>)>, Vec<ServerFailure>);
+
+/// Calls a paginated list `method` on every bound server that declares
+/// `capability`, concurrently and within DISCOVERY_TIMEOUT, and collects the
+/// items found under `field` per server
+pub async fn fan_out_list(
+    router: &Arc<Router>,
+    capability: &'static str,
+    method: &'static str,
+    field: &'static str,
+) -> Result<FanOutResult, String> {
+    let mut tasks = Vec::new();
+
+    for (mcp, binding) in router.bindings()? {
+        let env_vars = router.resolve_env_vars(&mcp, &binding);
+        let router = router.clone();
+        let task_mcp = mcp.clone();
+        let task_binding = binding.clone();
+        let handle = tokio::spawn(async move {
+            let client = router.client(&task_mcp, &task_binding, &env_vars).await?;
+            if !client.supports(capability) {
+                return Ok(Vec::new());
+            }
+            list_all(&client, method, field).await
+        });
+        tasks.push((mcp, binding, handle));
+    }
+
+    let deadline = tokio::time::Instant::now() + DISCOVERY_TIMEOUT;
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+
+    for (mcp, binding, handle) in tasks {
+        let result = match tokio::time::timeout_at(deadline, handle).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => Err(format!("Task failed: {}", e)),
+            Err(_) => Err(format!("Timed out after {}s", DISCOVERY_TIMEOUT.as_secs())),
+        };
+
+        match result {
+            Ok(items) => results.push((mcp, binding, items)),
+            Err(e) => {
+                eprintln!("[fan_out_list] {} failed for MCP {}: {}", method, mcp.name, e);
+                failures.push(ServerFailure {
+                    mcp_id: mcp.id.clone(),
+                    mcp_name: mcp.name.clone(),
+                    error: e,
+                });
+            }
+        }
+    }
+
+    Ok((results, failures))
+}
+
+/// `_meta` reporting the servers that failed, if any
+pub fn failures_meta(failures: Vec<ServerFailure>) -> Option<Value> {
+    if failures.is_empty() {
+        None
+    } else {
+        Some(json!({ "mcp-toolkit/failures": failures }))
+    }
+}
+
+/// Follows `nextCursor` until the server has returned every item
+async fn list_all(client: &McpClient, method: &str, field: &str) -> Result<Vec<Value>, String> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    for _ in 0..MAX_PAGES {
+        let params = match &cursor {
+            Some(c) => json!({ "cursor": c }),
+            None => json!({}),
+        };
+        let result = client.request(method, params).await?;
+
+        if let Some(page) = result.get(field).and_then(|p| p.as_array()) {
+            items.extend(page.iter().cloned());
+        }
+
+        match result.get("nextCursor").and_then(|c| c.as_str()) {
+            Some(next) => cursor = Some(next.to_string()),
+            None => return Ok(items),
+        }
+    }
+
+    eprintln!("[list_all] {} returned more than {} pages, truncating", method, MAX_PAGES);
+    Ok(items)
+}
@@ -0,0 +1,72 @@
+use crate::models::*;
+use crate::storage::Storage;
+use chrono::Utc;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The retention policy is applied at startup and after this many recorded calls
+const PRUNE_EVERY: u64 = 100;
+
+static RECORDED: AtomicU64 = AtomicU64::new(0);
+
+/// Applies the router log retention policy
+pub fn prune(storage: &Storage) {
+    match storage.prune_router_logs() {
+        Ok(0) => {}
+        Ok(n) => eprintln!("[CallLog] Pruned {} router log entries", n),
+        Err(e) => eprintln!("[CallLog] {}", e),
+    }
+}
+
+/// Records a routed tool call in the router_logs table. Failures are only
+/// logged, so a broken database never fails the call itself.
+pub fn record(
+    storage: &Storage,
+    mcp: &Mcp,
+    binding: &ProjectMcpBinding,
+    tool_name: &str,
+    args: &Value,
+    result: &Result<Value, String>,
+    duration: Duration,
+) {
+    let (status, error, result_size) = match result {
+        // The server ran the tool but reported that it failed
+        Ok(output) if output.get("isError").and_then(|v| v.as_bool()) == Some(true) => {
+            ("error", first_text(output), Some(output.to_string().len() as i64))
+        }
+        Ok(output) => ("success", None, Some(output.to_string().len() as i64)),
+        Err(e) => ("error", Some(e.clone()), None),
+    };
+
+    let log = RouterLog {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+        project_id: binding.project_id.clone(),
+        mcp_id: mcp.id.clone(),
+        tool_name: tool_name.to_string(),
+        status: status.to_string(),
+        duration_ms: duration.as_millis() as i64,
+        error,
+        args_size: Some(args.to_string().len() as i64),
+        result_size,
+    };
+
+    if let Err(e) = storage.insert_router_log(&log) {
+        eprintln!("[CallLog] {}", e);
+    }
+
+    if RECORDED.fetch_add(1, Ordering::Relaxed) % PRUNE_EVERY == PRUNE_EVERY - 1 {
+        prune(storage);
+    }
+}
+
+/// The first text block of a tool result, used as its error message
+fn first_text(output: &Value) -> Option<String> {
+    output
+        .get("content")?
+        .as_array()?
+        .iter()
+        .find_map(|c| c.get("text").and_then(|t| t.as_str()))
+        .map(|t| t.to_string())
+}
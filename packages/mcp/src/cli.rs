@@ -0,0 +1,53 @@
+use std::env;
+
+/// Environment variable used when `--project` is not passed
+pub const PROJECT_ENV_VAR: &str = "MCP_TOOLKIT_PROJECT";
+
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    /// Project id or name whose bindings the router exposes
+    pub project: Option<String>,
+    /// Address to serve Streamable HTTP on, from `serve --http <addr>`
+    pub http: Option<String>,
+}
+
+/// Parses the command line, falling back to environment variables
+pub fn parse_args() -> Result<CliArgs, String> {
+    let mut cli = CliArgs::default();
+    let mut args = env::args().skip(1);
+    let mut serve = false;
+
+    while let Some(arg) = args.next() {
+        if arg == "serve" && !serve {
+            serve = true;
+        } else if serve && arg == "--http" {
+            let value = args
+                .next()
+                .ok_or("Missing value for --http")?;
+            cli.http = Some(value);
+        } else if let Some(value) = arg.strip_prefix("--http=").filter(|_| serve) {
+            cli.http = Some(value.to_string());
+        } else if arg == "--project" {
+            let value = args
+                .next()
+                .ok_or("Missing value for --project")?;
+            cli.project = Some(value);
+        } else if let Some(value) = arg.strip_prefix("--project=") {
+            cli.project = Some(value.to_string());
+        } else {
+            return Err(format!("Unknown argument: {}", arg));
+        }
+    }
+
+    if serve && cli.http.is_none() {
+        return Err("serve requires --http <addr>".to_string());
+    }
+
+    if cli.project.is_none() {
+        cli.project = env::var(PROJECT_ENV_VAR)
+            .ok()
+            .filter(|v| !v.trim().is_empty());
+    }
+
+    Ok(cli)
+}
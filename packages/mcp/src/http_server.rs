@@ -0,0 +1,564 @@
+use crate::peer::ClientPeer;
+use crate::protocol_version;
+use crate::router::Router;
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Body, Bytes, Frame, Incoming};
+use hyper::header::{HeaderValue, ACCEPT, ALLOW, CACHE_CONTROL, CONTENT_TYPE, HOST, ORIGIN};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+const ENDPOINT_PATH: &str = "/mcp";
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// The stream opened with GET, carrying messages the server initiates
+const STANDALONE_STREAM: u64 = 0;
+
+/// Events kept per stream for clients resuming with Last-Event-ID
+const MAX_REPLAY_EVENTS: usize = 256;
+
+/// Finished POST response streams kept per session for resumption
+const MAX_FINISHED_STREAMS: usize = 32;
+
+/// Sessions without requests or a connected stream for this long are closed,
+/// for clients that disconnect without a DELETE
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How often idle sessions are looked for
+const SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+type HttpBody = BoxBody<Bytes, Infallible>;
+
+/// Serves the router over MCP Streamable HTTP until interrupted
+pub async fn serve(addr: &str, router: Arc<Router>) -> Result<(), String> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+    let local_addr = listener.local_addr().map_err(|e| e.to_string())?;
+    eprintln!("[HttpServer] Listening on http://{}{}", local_addr, ENDPOINT_PATH);
+
+    let server = Arc::new(HttpServer {
+        router,
+        sessions: Mutex::new(HashMap::new()),
+    });
+
+    let sweeper = server.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SESSION_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            sweeper.close_idle_sessions();
+        }
+    });
+
+    loop {
+        let (stream, remote) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    eprintln!("[HttpServer] Failed to accept connection: {}", e);
+                    continue;
+                }
+            },
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("[HttpServer] Interrupted, shutting down");
+                return Ok(());
+            }
+        };
+
+        let server = server.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |req| {
+                let server = server.clone();
+                async move { Ok::<_, Infallible>(server.handle(req).await) }
+            });
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                eprintln!("[HttpServer] Connection from {} failed: {}", remote, e);
+            }
+        });
+    }
+}
+
+struct HttpServer {
+    /// Template for new sessions; shares the pool and caches with them
+    router: Arc<Router>,
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+}
+
+impl HttpServer {
+    async fn handle(&self, req: Request<Incoming>) -> Response<HttpBody> {
+        if !origin_allowed(&req) {
+            return text_response(StatusCode::FORBIDDEN, "Origin not allowed");
+        }
+        if req.uri().path() != ENDPOINT_PATH {
+            return text_response(StatusCode::NOT_FOUND, "Not found");
+        }
+        // Absent on initialize and from 2025-03-26 clients, which is fine
+        if let Some(version) = header(&req, PROTOCOL_VERSION_HEADER) {
+            if !protocol_version::is_supported(version) {
+                return text_response(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Unsupported MCP-Protocol-Version: {}", version),
+                );
+            }
+        }
+
+        match *req.method() {
+            Method::POST => self.handle_post(req).await,
+            Method::GET => self.handle_get(&req),
+            Method::DELETE => self.handle_delete(&req),
+            _ => {
+                let mut response = text_response(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed");
+                response
+                    .headers_mut()
+                    .insert(ALLOW, HeaderValue::from_static("GET, POST, DELETE"));
+                response
+            }
+        }
+    }
+
+    /// A client message: requests are answered with JSON or an SSE stream,
+    /// notifications and responses are acknowledged with 202
+    async fn handle_post(&self, req: Request<Incoming>) -> Response<HttpBody> {
+        let wants_sse = accepts(&req, "text/event-stream");
+        let session_id = header(&req, SESSION_ID_HEADER).map(str::to_string);
+
+        let body = match req.into_body().collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(e) => return text_response(StatusCode::BAD_REQUEST, &format!("Failed to read body: {}", e)),
+        };
+        let message: Value = match serde_json::from_slice(&body) {
+            Ok(message) => message,
+            Err(e) => return jsonrpc_error(StatusCode::BAD_REQUEST, -32700, &format!("Parse error: {}", e)),
+        };
+        if !message.is_object() {
+            return jsonrpc_error(StatusCode::BAD_REQUEST, -32600, "Expected a single JSON-RPC message");
+        }
+
+        let method = message.get("method").and_then(|m| m.as_str());
+        let session = match session_id {
+            Some(id) => match self.sessions.lock().unwrap().get(&id) {
+                Some(session) => session.clone(),
+                None => return text_response(StatusCode::NOT_FOUND, "Unknown session"),
+            },
+            None if method == Some("initialize") => self.create_session(),
+            None => return text_response(StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header"),
+        };
+        session.touch();
+
+        // A response to a request we sent the client (e.g. roots/list)
+        if method.is_none() {
+            session.peer.handle_response(&message);
+            return accepted();
+        }
+
+        let input = message.to_string();
+
+        if message.get("id").is_none() {
+            session.dispatch(&input).await;
+            return accepted();
+        }
+
+        if wants_sse {
+            let (stream_id, body) = session.open_stream();
+            let task_session = session.clone();
+            let response = session.dispatch(&input);
+            tokio::spawn(async move {
+                let response = response.await;
+                // Empty when the client cancelled the request
+                if !response.is_empty() {
+                    task_session.publish(stream_id, &response);
+                }
+                task_session.finish_stream(stream_id);
+            });
+            return sse_response(body, &session.id);
+        }
+
+        let response = session.dispatch(&input).await;
+        if response.is_empty() {
+            return accepted();
+        }
+        let mut http_response = Response::new(full(response));
+        set_header(&mut http_response, CONTENT_TYPE.as_str(), "application/json");
+        set_header(&mut http_response, SESSION_ID_HEADER, &session.id);
+        http_response
+    }
+
+    /// Opens the standalone stream, or resumes a stream after Last-Event-ID
+    fn handle_get(&self, req: &Request<Incoming>) -> Response<HttpBody> {
+        if !accepts(req, "text/event-stream") {
+            return text_response(StatusCode::NOT_ACCEPTABLE, "Accept must include text/event-stream");
+        }
+        let session = match self.session_for(req) {
+            Ok(session) => session,
+            Err((status, message)) => return text_response(status, message),
+        };
+        session.touch();
+
+        let body = session.attach(header(req, LAST_EVENT_ID_HEADER));
+        sse_response(body, &session.id)
+    }
+
+    /// Ends a session at the client's request
+    fn handle_delete(&self, req: &Request<Incoming>) -> Response<HttpBody> {
+        let session = match self.session_for(req) {
+            Ok(session) => session,
+            Err((status, message)) => return text_response(status, message),
+        };
+
+        self.close_session(&session.id);
+        text_response(StatusCode::OK, "Session closed")
+    }
+
+    /// Forgets a session and releases the downstream clients only it was using
+    fn close_session(&self, id: &str) {
+        self.sessions.lock().unwrap().remove(id);
+        self.router.pool.release(id);
+        eprintln!("[HttpServer] Session closed: {}", id);
+    }
+
+    fn close_idle_sessions(&self) {
+        let idle: Vec<String> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|session| session.is_idle(SESSION_IDLE_TIMEOUT))
+            .map(|session| session.id.clone())
+            .collect();
+
+        for id in idle {
+            eprintln!("[HttpServer] Session {} has been idle for {}s", id, SESSION_IDLE_TIMEOUT.as_secs());
+            self.close_session(&id);
+        }
+    }
+
+    fn session_for(&self, req: &Request<Incoming>) -> Result<Arc<Session>, (StatusCode, &'static str)> {
+        let id = header(req, SESSION_ID_HEADER)
+            .ok_or((StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header"))?;
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .ok_or((StatusCode::NOT_FOUND, "Unknown session"))
+    }
+
+    fn create_session(&self) -> Arc<Session> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+
+        let peer = Arc::new(ClientPeer::new(tx));
+        let router = self.router.new_session(&id);
+        router.set_upstream(peer.upstream_handler());
+        router.set_notifier(peer.notification_handler());
+
+        let session = Arc::new(Session {
+            id: id.clone(),
+            router: Arc::new(router),
+            peer,
+            streams: Mutex::new(Streams::default()),
+            last_active: Mutex::new(Instant::now()),
+        });
+
+        // Messages the server initiates go out on the standalone stream
+        let weak = Arc::downgrade(&session);
+        tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                match weak.upgrade() {
+                    Some(session) => session.publish(STANDALONE_STREAM, &message),
+                    None => break,
+                }
+            }
+        });
+
+        eprintln!("[HttpServer] Session opened: {}", id);
+        self.sessions.lock().unwrap().insert(id, session.clone());
+        session
+    }
+}
+
+/// One client connection, with its own project scope and event streams
+struct Session {
+    id: String,
+    router: Arc<Router>,
+    peer: Arc<ClientPeer>,
+    streams: Mutex<Streams>,
+    /// When the client last sent a message or finished a request
+    last_active: Mutex<Instant>,
+}
+
+#[derive(Default)]
+struct Streams {
+    next_stream_id: u64,
+    next_event_id: u64,
+    streams: HashMap<u64, EventStream>,
+}
+
+#[derive(Default)]
+struct EventStream {
+    /// Recent events by sequence number, replayed on resumption
+    events: VecDeque<(u64, String)>,
+    /// The connected client, if any
+    live: Option<mpsc::UnboundedSender<Bytes>>,
+    finished: bool,
+}
+
+impl Session {
+    /// Handles one JSON-RPC message and returns the serialized response, if any
+    fn dispatch(self: &Arc<Self>, input: &str) -> impl Future<Output = String> {
+        let response = crate::handle_request(input, &self.router, &self.peer);
+        let session = self.clone();
+        async move {
+            let response = response.await;
+            // A long request counts as activity until it ends
+            session.touch();
+            match response {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("[HttpServer] Error handling request: {}", e);
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": {
+                            "code": -32603,
+                            "message": format!("Internal error: {}", e)
+                        }
+                    })
+                    .to_string()
+                }
+            }
+        }
+    }
+
+    fn touch(&self) {
+        *self.last_active.lock().unwrap() = Instant::now();
+    }
+
+    /// Whether the client has been silent for `timeout` and has no stream
+    /// connected on which it could still be waiting for messages
+    fn is_idle(&self, timeout: Duration) -> bool {
+        if self.last_active.lock().unwrap().elapsed() < timeout {
+            return false;
+        }
+        let streams = self.streams.lock().unwrap();
+        !streams
+            .streams
+            .values()
+            .any(|stream| stream.live.as_ref().is_some_and(|live| !live.is_closed()))
+    }
+
+    /// Starts the SSE stream answering a POSTed request
+    fn open_stream(&self) -> (u64, HttpBody) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut streams = self.streams.lock().unwrap();
+        streams.next_stream_id += 1;
+        let stream_id = streams.next_stream_id;
+        streams.streams.insert(
+            stream_id,
+            EventStream {
+                live: Some(tx),
+                ..Default::default()
+            },
+        );
+        (stream_id, SseBody { rx }.boxed())
+    }
+
+    /// Records a message on a stream and sends it to the client if connected
+    fn publish(&self, stream_id: u64, message: &str) {
+        let mut streams = self.streams.lock().unwrap();
+        streams.next_event_id += 1;
+        let seq = streams.next_event_id;
+
+        let stream = streams.streams.entry(stream_id).or_default();
+        stream.events.push_back((seq, message.to_string()));
+        if stream.events.len() > MAX_REPLAY_EVENTS {
+            stream.events.pop_front();
+        }
+
+        if let Some(live) = &stream.live {
+            if live.send(format_event(stream_id, seq, message)).is_err() {
+                // The client disconnected; it may resume with Last-Event-ID
+                stream.live = None;
+            }
+        }
+    }
+
+    /// Closes a POST response stream once its response was sent
+    fn finish_stream(&self, stream_id: u64) {
+        let mut streams = self.streams.lock().unwrap();
+        if let Some(stream) = streams.streams.get_mut(&stream_id) {
+            stream.finished = true;
+            stream.live = None;
+        }
+
+        let mut finished: Vec<u64> = streams
+            .streams
+            .iter()
+            .filter(|(_, s)| s.finished)
+            .map(|(id, _)| *id)
+            .collect();
+        if finished.len() > MAX_FINISHED_STREAMS {
+            finished.sort_unstable();
+            for id in &finished[..finished.len() - MAX_FINISHED_STREAMS] {
+                streams.streams.remove(id);
+            }
+        }
+    }
+
+    /// Connects a GET request: replays what the client missed on the stream
+    /// named by Last-Event-ID, or (re)opens the standalone stream
+    fn attach(&self, last_event_id: Option<&str>) -> HttpBody {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut streams = self.streams.lock().unwrap();
+
+        let resumed = last_event_id
+            .and_then(parse_event_id)
+            .and_then(|(stream_id, seq)| Some((stream_id, seq, streams.streams.get_mut(&stream_id)?)));
+
+        match resumed {
+            Some((stream_id, seq, stream)) => {
+                eprintln!("[HttpServer] Resuming stream {} of session {} after event {}", stream_id, self.id, seq);
+                for (event_seq, message) in stream.events.iter().filter(|(s, _)| *s > seq) {
+                    let _ = tx.send(format_event(stream_id, *event_seq, message));
+                }
+                // A finished stream ends after the replay
+                if !stream.finished {
+                    stream.live = Some(tx);
+                }
+            }
+            None => {
+                streams.streams.entry(STANDALONE_STREAM).or_default().live = Some(tx);
+            }
+        }
+
+        SseBody { rx }.boxed()
+    }
+}
+
+/// Streams SSE events from a channel until the sender is dropped
+struct SseBody {
+    rx: mpsc::UnboundedReceiver<Bytes>,
+}
+
+impl Body for SseBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        self.rx.poll_recv(cx).map(|event| event.map(|e| Ok(Frame::data(e))))
+    }
+}
+
+/// Event ids are `<stream>-<seq>`, so a resuming client names its stream
+fn format_event(stream_id: u64, seq: u64, message: &str) -> Bytes {
+    let mut event = format!("id: {}-{}\nevent: message\n", stream_id, seq);
+    for line in message.lines() {
+        event.push_str("data: ");
+        event.push_str(line);
+        event.push('\n');
+    }
+    event.push('\n');
+    Bytes::from(event)
+}
+
+fn parse_event_id(id: &str) -> Option<(u64, u64)> {
+    let (stream_id, seq) = id.split_once('-')?;
+    Some((stream_id.parse().ok()?, seq.parse().ok()?))
+}
+
+/// Browsers must come from the origin they're talking to, which stops DNS
+/// rebinding; other clients send no Origin
+fn origin_allowed(req: &Request<Incoming>) -> bool {
+    let origin = match header(req, ORIGIN.as_str()) {
+        Some(origin) => origin,
+        None => return true,
+    };
+    let host = header(req, HOST.as_str()).unwrap_or("");
+    origin.split_once("://").map(|(_, authority)| authority) == Some(host)
+}
+
+fn accepts(req: &Request<Incoming>, content_type: &str) -> bool {
+    header(req, ACCEPT.as_str()).is_some_and(|accept| accept.contains(content_type))
+}
+
+fn header<'a>(req: &'a Request<Incoming>, name: &str) -> Option<&'a str> {
+    req.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+fn set_header(response: &mut Response<HttpBody>, name: &'static str, value: &str) {
+    if let Ok(value) = HeaderValue::from_str(value) {
+        response.headers_mut().insert(name, value);
+    }
+}
+
+fn full(body: String) -> HttpBody {
+    Full::new(Bytes::from(body)).boxed()
+}
+
+fn text_response(status: StatusCode, message: &str) -> Response<HttpBody> {
+    let mut response = Response::new(full(message.to_string()));
+    *response.status_mut() = status;
+    response
+}
+
+fn jsonrpc_error(status: StatusCode, code: i32, message: &str) -> Response<HttpBody> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": { "code": code, "message": message }
+    });
+    let mut response = text_response(status, &body.to_string());
+    set_header(&mut response, CONTENT_TYPE.as_str(), "application/json");
+    response
+}
+
+fn accepted() -> Response<HttpBody> {
+    let mut response = Response::new(full(String::new()));
+    *response.status_mut() = StatusCode::ACCEPTED;
+    response
+}
+
+fn sse_response(body: HttpBody, session_id: &str) -> Response<HttpBody> {
+    let mut response = Response::new(body);
+    set_header(&mut response, CONTENT_TYPE.as_str(), "text/event-stream");
+    set_header(&mut response, CACHE_CONTROL.as_str(), "no-cache");
+    set_header(&mut response, SESSION_ID_HEADER, session_id);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_ids_round_trip() {
+        let event = format_event(3, 42, "{\"a\":1}");
+        assert_eq!(
+            event,
+            Bytes::from("id: 3-42\nevent: message\ndata: {\"a\":1}\n\n")
+        );
+        assert_eq!(parse_event_id("3-42"), Some((3, 42)));
+        assert_eq!(parse_event_id("42"), None);
+    }
+}
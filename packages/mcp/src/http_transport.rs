@@ -0,0 +1,332 @@
+use crate::models::*;
+use crate::sse::SseParser;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// Best-effort deadline for the DELETE that ends a session
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Deadline for reaching the server. Whole requests aren't limited here since
+/// event streams stay open; callers bound the exchanges they wait on.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// MCP Streamable HTTP transport: every message is POSTed to one endpoint and
+/// replies come back either as a JSON body or as an SSE stream
+pub struct StreamableHttpTransport {
+    client: reqwest::Client,
+    url: String,
+    /// Built from `header_*` env vars, e.g. `header_Authorization`
+    headers: HeaderMap,
+    session_id: Mutex<Option<String>>,
+    protocol_version: Mutex<Option<String>>,
+    /// Set when the server dropped our session; the pool will reconnect
+    broken: AtomicBool,
+    /// Set when the initialize POST got a 4xx, i.e. the server may only speak HTTP+SSE
+    rejected_initialize: AtomicBool,
+    /// Receives every message the server sends
+    incoming: mpsc::UnboundedSender<Value>,
+    /// The GET stream carrying messages the server initiates
+    listener: Mutex<Option<JoinHandle<()>>>,
+    mcp_name: String,
+}
+
+impl StreamableHttpTransport {
+    pub fn new(
+        mcp: &Mcp,
+        env_vars: &[EnvVar],
+        incoming: mpsc::UnboundedSender<Value>,
+    ) -> Result<Self, String> {
+        let url = mcp
+            .config
+            .http_url
+            .as_ref()
+            .ok_or("No HTTP URL specified")?
+            .clone();
+
+        Ok(StreamableHttpTransport {
+            client: http_client()?,
+            url,
+            headers: headers_from_env(env_vars)?,
+            session_id: Mutex::new(None),
+            protocol_version: Mutex::new(None),
+            broken: AtomicBool::new(false),
+            rejected_initialize: AtomicBool::new(false),
+            incoming,
+            listener: Mutex::new(None),
+            mcp_name: mcp.name.clone(),
+        })
+    }
+
+    /// Sent as `MCP-Protocol-Version` on every request after initialize
+    pub fn set_protocol_version(&self, version: &str) {
+        *self.protocol_version.lock().unwrap() = Some(version.to_string());
+    }
+
+    /// POST a message. Replies, and anything the server sends before them,
+    /// are forwarded to `incoming` as they arrive.
+    pub async fn send(&self, message: &Value) -> Result<(), String> {
+        let response = self.post(message).await?;
+
+        // Notifications and responses are acknowledged with 202 Accepted
+        if response.status() == reqwest::StatusCode::ACCEPTED {
+            return Ok(());
+        }
+
+        if is_event_stream(&response) {
+            tokio::spawn(read_event_stream(
+                response,
+                self.incoming.clone(),
+                message.get("id").cloned(),
+                self.mcp_name.clone(),
+            ));
+            return Ok(());
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read response from {}: {}", self.mcp_name, e))?;
+        if body.trim().is_empty() {
+            return Ok(());
+        }
+        eprintln!("[StreamableHttpTransport] <<< {}", body);
+
+        let reply: Value =
+            serde_json::from_str(&body).map_err(|e| format!("Failed to parse response: {}", e))?;
+        let _ = self.incoming.send(reply);
+        Ok(())
+    }
+
+    async fn post(&self, message: &Value) -> Result<reqwest::Response, String> {
+        let body = message.to_string();
+        eprintln!("[StreamableHttpTransport] >>> {}", body);
+
+        let mut req = self
+            .client
+            .post(&self.url)
+            .headers(self.headers.clone())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .header(CONTENT_TYPE, "application/json")
+            .body(body);
+
+        let session_id = self.session_id.lock().unwrap().clone();
+        if let Some(session_id) = &session_id {
+            req = req.header(SESSION_ID_HEADER, session_id);
+        }
+        if let Some(version) = self.protocol_version.lock().unwrap().clone() {
+            req = req.header(PROTOCOL_VERSION_HEADER, version);
+        }
+
+        let response = req
+            .send()
+            .await
+            .map_err(|e| format!("HTTP request to {} failed: {}", self.mcp_name, e))?;
+
+        // The server assigns the session on the initialize response
+        if let Some(id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.session_id.lock().unwrap() = Some(id.to_string());
+        }
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND && session_id.is_some() {
+            self.broken.store(true, Ordering::SeqCst);
+            return Err(format!("Session expired on {}", self.mcp_name));
+        }
+        if status.is_client_error() && message.get("method") == Some(&json!("initialize")) {
+            self.rejected_initialize.store(true, Ordering::SeqCst);
+        }
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("HTTP error {} from {}: {}", status, self.mcp_name, text));
+        }
+
+        Ok(response)
+    }
+
+    /// Opens the optional GET stream on which the server sends requests and
+    /// notifications unrelated to any of ours
+    pub fn listen(&self) {
+        let mut req = self
+            .client
+            .get(&self.url)
+            .headers(self.headers.clone())
+            .header(ACCEPT, "text/event-stream");
+        if let Some(session_id) = self.session_id.lock().unwrap().clone() {
+            req = req.header(SESSION_ID_HEADER, session_id);
+        }
+        if let Some(version) = self.protocol_version.lock().unwrap().clone() {
+            req = req.header(PROTOCOL_VERSION_HEADER, version);
+        }
+
+        let incoming = self.incoming.clone();
+        let mcp_name = self.mcp_name.clone();
+        let handle = tokio::spawn(async move {
+            match req.send().await {
+                Ok(response) if response.status().is_success() && is_event_stream(&response) => {
+                    read_event_stream(response, incoming, None, mcp_name).await;
+                }
+                // 405 means the server doesn't offer one
+                Ok(response) => eprintln!(
+                    "[StreamableHttpTransport] No server stream for {} ({})",
+                    mcp_name,
+                    response.status()
+                ),
+                Err(e) => eprintln!(
+                    "[StreamableHttpTransport] Failed to open server stream for {}: {}",
+                    mcp_name, e
+                ),
+            }
+        });
+
+        if let Some(previous) = self.listener.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+    }
+
+    pub fn rejected_initialize(&self) -> bool {
+        self.rejected_initialize.load(Ordering::SeqCst)
+    }
+
+    pub fn is_alive(&self) -> bool {
+        !self.broken.load(Ordering::SeqCst)
+    }
+
+    /// Ends the session with a DELETE, as the spec asks clients to do
+    pub async fn close(&self) {
+        self.stop_listening();
+        if let Some(request) = self.delete_request() {
+            let _ = tokio::time::timeout(CLOSE_TIMEOUT, request.send()).await;
+        }
+    }
+
+    /// Like `close`, but without waiting; used when the client is dropped
+    pub fn shutdown(&self) -> Result<(), String> {
+        self.stop_listening();
+        if let Some(request) = self.delete_request() {
+            let handle = tokio::runtime::Handle::try_current()
+                .map_err(|_| "No runtime to end the HTTP session".to_string())?;
+            handle.spawn(async move {
+                let _ = tokio::time::timeout(CLOSE_TIMEOUT, request.send()).await;
+            });
+        }
+        Ok(())
+    }
+
+    fn stop_listening(&self) {
+        if let Some(listener) = self.listener.lock().unwrap().take() {
+            listener.abort();
+        }
+    }
+
+    fn delete_request(&self) -> Option<reqwest::RequestBuilder> {
+        let session_id = self.session_id.lock().unwrap().take()?;
+        eprintln!("[StreamableHttpTransport] Ending session for: {}", self.mcp_name);
+        Some(
+            self.client
+                .delete(&self.url)
+                .headers(self.headers.clone())
+                .header(SESSION_ID_HEADER, session_id),
+        )
+    }
+}
+
+fn is_event_stream(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"))
+}
+
+/// Forwards every message on an SSE stream. If the stream answers `request_id`
+/// but ends without the response, an error response is forwarded instead so
+/// the caller doesn't wait for its timeout.
+async fn read_event_stream(
+    mut response: reqwest::Response,
+    incoming: mpsc::UnboundedSender<Value>,
+    request_id: Option<Value>,
+    mcp_name: String,
+) {
+    let mut parser = SseParser::new();
+    let mut answered = request_id.is_none();
+
+    loop {
+        let chunk = match response.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("[StreamableHttpTransport] Event stream from {} failed: {}", mcp_name, e);
+                break;
+            }
+        };
+
+        for event in parser.feed(&chunk) {
+            if event.event.as_deref().is_some_and(|e| e != "message") {
+                continue;
+            }
+
+            eprintln!("[StreamableHttpTransport] <<< {}", event.data);
+
+            let message: Value = match serde_json::from_str(&event.data) {
+                Ok(message) => message,
+                Err(e) => {
+                    eprintln!("[StreamableHttpTransport] Skipping invalid event data: {}", e);
+                    continue;
+                }
+            };
+
+            if message.get("method").is_none() && message.get("id") == request_id.as_ref() {
+                answered = true;
+            }
+            if incoming.send(message).is_err() {
+                return;
+            }
+        }
+    }
+
+    if !answered {
+        let _ = incoming.send(json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "error": {
+                "code": -32000,
+                "message": format!("Event stream from {} ended without a response", mcp_name)
+            }
+        }));
+    }
+}
+
+/// A client for talking to HTTP MCP servers
+pub fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Env vars named `header_<Name>` become HTTP headers; the rest are ignored
+pub fn headers_from_env(env_vars: &[EnvVar]) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    for env_var in env_vars {
+        if env_var.key.to_lowercase().starts_with("header_") {
+            let name = HeaderName::from_bytes(&env_var.key.as_bytes()[7..])
+                .map_err(|e| format!("Invalid header name '{}': {}", &env_var.key[7..], e))?;
+            let value = HeaderValue::from_str(&env_var.value)
+                .map_err(|e| format!("Invalid value for header '{}': {}", name, e))?;
+            headers.insert(name, value);
+        }
+    }
+    Ok(headers)
+}
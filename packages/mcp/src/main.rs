@@ -1,18 +1,65 @@
+mod aggregate;
+mod call_log;
+mod cli;
 mod executor;
+mod http_server;
+mod http_transport;
 mod mcp_client;
 mod mcp_protocol;
 mod models;
+mod naming;
+mod peer;
+mod pool;
+mod projects;
+mod prompts;
+mod protocol_version;
+mod resources;
+mod router;
 mod secrets;
+mod sse;
+mod sse_transport;
+mod stdio_transport;
 mod storage;
+mod tool_args;
+mod tool_cache;
+mod tool_filter;
+mod tool_overrides;
+mod toolkit;
 
 use mcp_protocol::*;
+use aggregate::DISCOVERY_TIMEOUT;
 use models::*;
+use mcp_client::ProgressHandler;
+use peer::ClientPeer;
+use pool::PoolKey;
+use router::Router;
 use secrets::{get_or_create_key, SecretManager};
 use serde_json::{json, Value};
-use std::io::{self, BufRead, Write as IoWrite};
+use std::collections::HashSet;
+use std::future::Future;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use storage::Storage;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tool_cache::CacheKey;
+
+/// How long requests still running when the client disconnects may take to finish
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
 
 fn main() {
+    let cli = match cli::parse_args() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: mcp-toolkit [serve --http <addr>] [--project <id|name>]");
+            std::process::exit(2);
+        }
+    };
+
     // Initialize secret manager
     let key = match get_or_create_key() {
         Ok(k) => k,
@@ -33,6 +80,30 @@ fn main() {
         }
     };
 
+    // Resolve the project whose bindings this router exposes
+    let project = match projects::resolve_project(&storage, cli.project.as_deref()) {
+        Ok(Some(p)) => {
+            eprintln!("Serving project: {} ({})", p.name, p.id);
+            Some(p)
+        }
+        Ok(None) => {
+            eprintln!(
+                "No project selected (use --project or {}) and none matches the working directory, serving all enabled bindings",
+                cli::PROJECT_ENV_VAR
+            );
+            None
+        }
+        Err(e) => {
+            eprintln!("Failed to select project: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    call_log::prune(&storage);
+
+    let pinned = cli.project.is_some();
+    let router = Arc::new(Router::new(storage, secret_manager, project, pinned));
+
     // Create Tokio runtime for async operations
     let runtime = match tokio::runtime::Runtime::new() {
         Ok(r) => r,
@@ -42,46 +113,73 @@ fn main() {
         }
     };
 
+    match cli.http.as_deref() {
+        Some(addr) => {
+            eprintln!("MCP Toolkit HTTP server starting...");
+            if let Err(e) = runtime.block_on(http_server::serve(addr, router.clone())) {
+                eprintln!("HTTP server error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => serve_stdio(&runtime, &router),
+    }
+
+    runtime.block_on(router.pool.shutdown_all());
+    eprintln!("Server exiting...");
+}
+
+/// Serves one client on line-delimited JSON-RPC over stdin/stdout. Requests
+/// run concurrently and their responses are written as they complete.
+fn serve_stdio(runtime: &tokio::runtime::Runtime, router: &Arc<Router>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let writer = runtime.spawn(write_stdout(rx));
     eprintln!("MCP Toolkit server starting...");
 
-    // Process stdin/stdout
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
-    let mut stderr = io::stderr();
+    serve_lines(runtime, router, io::stdin().lock(), tx);
 
-    for line in stdin.lock().lines() {
+    runtime.block_on(async {
+        let _ = tokio::time::timeout(SHUTDOWN_GRACE, writer).await;
+    });
+}
+
+/// Handles the messages read from `input`, one per line, until it ends.
+/// Messages for the client are sent to `output`.
+fn serve_lines(
+    runtime: &tokio::runtime::Runtime,
+    router: &Arc<Router>,
+    input: impl BufRead,
+    output: mpsc::UnboundedSender<String>,
+) {
+    // Notifications are handled on this thread and may spawn tasks
+    let _runtime_context = runtime.enter();
+    let peer = Arc::new(ClientPeer::new(output));
+    router.set_upstream(peer.upstream_handler());
+    router.set_notifier(peer.notification_handler());
+    let mut in_flight = JoinSet::new();
+
+    for line in input.lines() {
         match line {
             Ok(input) => {
                 eprintln!("Received input: {}", input);
-                let response = runtime.block_on(async {
-                    handle_request(&input, &storage, &secret_manager).await
-                });
 
-                match response {
-                    Ok(resp) => {
-                        if !resp.is_empty() {
-                            eprintln!("Sending response: {}", resp);
-                            if let Err(e) = writeln!(stdout, "{}", resp) {
-                                let _ = writeln!(stderr, "Error writing response: {}", e);
-                            }
-                            let _ = stdout.flush();
-                        } else {
-                            eprintln!("Empty response (notification acknowledged)");
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Error handling request: {}", e);
-                        let error_response = json!({
-                            "jsonrpc": "2.0",
-                            "id": null,
-                            "error": {
-                                "code": -32603,
-                                "message": format!("Internal error: {}", e)
-                            }
-                        });
-                        let _ = writeln!(stdout, "{}", error_response);
-                        let _ = stdout.flush();
-                    }
+                let message = serde_json::from_str::<Value>(&input).ok();
+
+                // Responses to requests we sent the client (e.g. roots/list)
+                if message.as_ref().is_some_and(|m| peer.handle_response(m)) {
+                    continue;
+                }
+
+                // Reap handlers that already finished
+                while in_flight.try_join_next().is_some() {}
+
+                // initialize and notifications are handled in order, before
+                // anything the client sends after them
+                let method = message.as_ref().and_then(|m| m.get("method")).and_then(|m| m.as_str());
+                let is_request = message.as_ref().and_then(|m| m.get("id")).is_some();
+                if !is_request || method == Some("initialize") {
+                    runtime.block_on(respond(&input, router, &peer));
+                } else {
+                    in_flight.spawn_on(respond(&input, router, &peer), runtime.handle());
                 }
             }
             Err(e) => {
@@ -90,41 +188,156 @@ fn main() {
             }
         }
     }
-    eprintln!("Server exiting...");
+
+    // The client closed stdin; let requests already in progress finish
+    runtime.block_on(async {
+        if tokio::time::timeout(SHUTDOWN_GRACE, async {
+            while in_flight.join_next().await.is_some() {}
+        })
+        .await
+        .is_err()
+        {
+            eprintln!("Abandoning {} unfinished requests", in_flight.len());
+            in_flight.shutdown().await;
+        }
+    });
+}
+
+/// The single writer for stdout, so concurrent responses never interleave
+async fn write_stdout(mut rx: mpsc::UnboundedReceiver<String>) {
+    let mut stdout = tokio::io::stdout();
+    while let Some(message) = rx.recv().await {
+        let line = format!("{}\n", message);
+        if let Err(e) = stdout.write_all(line.as_bytes()).await {
+            eprintln!("Error writing message: {}", e);
+            break;
+        }
+        let _ = stdout.flush().await;
+    }
+}
+
+/// Handles one message from the client and sends back the response, if any
+fn respond(input: &str, router: &Arc<Router>, peer: &Arc<ClientPeer>) -> impl Future<Output = ()> {
+    let response = handle_request(input, router, peer);
+    let peer = peer.clone();
+    async move {
+        match response.await {
+            Ok(resp) => {
+                if !resp.is_empty() {
+                    peer.send(&resp);
+                } else {
+                    eprintln!("Empty response (notification acknowledged)");
+                }
+            }
+            Err(e) => {
+                eprintln!("Error handling request: {}", e);
+                let error_response = json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": {
+                        "code": -32603,
+                        "message": format!("Internal error: {}", e)
+                    }
+                });
+                peer.send(&error_response.to_string());
+            }
+        }
+    }
 }
 
-async fn handle_request(
+/// Handles one message from the client. Notifications take effect and
+/// requests become cancellable right away, before the returned future is
+/// first polled, so a cancellation can't overtake the request it cancels.
+fn handle_request(
     input: &str,
-    storage: &Storage,
-    secret_manager: &SecretManager,
-) -> Result<String, String> {
-    let request: JsonRpcRequest = serde_json::from_str(input)
-        .map_err(|e| format!("Failed to parse JSON-RPC request: {}", e))?;
+    router: &Arc<Router>,
+    peer: &Arc<ClientPeer>,
+) -> impl Future<Output = Result<String, String>> + Send + 'static {
+    let parsed = serde_json::from_str::<JsonRpcRequest>(input)
+        .map_err(|e| format!("Failed to parse JSON-RPC request: {}", e));
+
+    // Handle notifications (no response needed, not even an error)
+    let in_progress = match &parsed {
+        Ok(request) => match &request.id {
+            Some(request_id) => Some(peer.track(request_id)),
+            None => {
+                handle_notification(request, router, peer);
+                None
+            }
+        },
+        Err(_) => None,
+    };
 
-    let id = request.id.clone();
+    let router = router.clone();
+    let peer = peer.clone();
+    async move {
+        let request = parsed?;
+        let (Some(request_id), Some(mut in_progress)) = (request.id.clone(), in_progress) else {
+            return Ok("".to_string());
+        };
+
+        let response = tokio::select! {
+            response = dispatch_request(&request, &router, &peer) => response,
+            Ok(()) = &mut in_progress.cancelled => {
+                // Dropping the handler cancels any downstream request it made
+                eprintln!("Request {} cancelled by the client", request_id);
+                return Ok("".to_string());
+            }
+        };
 
-    // Handle notifications (no response needed)
-    if request.method == "notifications/initialized" {
-        return Ok("".to_string());
+        serde_json::to_string(&response).map_err(|e| format!("Failed to serialize response: {}", e))
     }
+}
 
-    let response = match request.method.as_str() {
-        "initialize" => handle_initialize(id, &request),
-        "tools/list" => handle_tools_list(id, storage),
-        "tools/call" => {
-            handle_tools_call(id, &request, storage, secret_manager).await
+fn handle_notification(request: &JsonRpcRequest, router: &Arc<Router>, peer: &Arc<ClientPeer>) {
+    match request.method.as_str() {
+        "notifications/initialized" | "notifications/roots/list_changed" => {
+            if peer.supports("roots") {
+                tokio::spawn(refresh_roots(router.clone(), peer.clone()));
+            }
+        }
+        "notifications/cancelled" => {
+            let request_id = request.params.as_ref().and_then(|p| p.get("requestId"));
+            if let Some(request_id) = request_id {
+                if !peer.cancel(request_id) {
+                    eprintln!("Ignoring cancellation of finished or unknown request {}", request_id);
+                }
+            }
+        }
+        method => eprintln!("Ignoring notification: {}", method),
+    }
+}
+
+async fn dispatch_request(
+    request: &JsonRpcRequest,
+    router: &Arc<Router>,
+    peer: &Arc<ClientPeer>,
+) -> JsonRpcResponse {
+    let id = request.id.clone();
+    match request.method.as_str() {
+        "initialize" => handle_initialize(id, request, peer),
+        "ping" => JsonRpcResponse::success(id, json!({})),
+        "tools/list" => handle_tools_list(id, router, peer).await,
+        "tools/call" => handle_tools_call(id, request, router, peer).await,
+        "resources/list" => resources::handle_resources_list(id, router).await,
+        "resources/templates/list" => {
+            resources::handle_resource_templates_list(id, router).await
+        }
+        "resources/read" => resources::handle_resources_read(id, request, router).await,
+        "resources/subscribe" | "resources/unsubscribe" => {
+            resources::handle_resources_subscription(id, request, router).await
         }
+        "prompts/list" => prompts::handle_prompts_list(id, router).await,
+        "prompts/get" => prompts::handle_prompts_get(id, request, router).await,
         _ => JsonRpcResponse::error(
             id,
             -32601,
             format!("Method not found: {}", request.method),
         ),
-    };
-
-    serde_json::to_string(&response).map_err(|e| format!("Failed to serialize response: {}", e))
+    }
 }
 
-fn handle_initialize(id: Option<Value>, request: &JsonRpcRequest) -> JsonRpcResponse {
+fn handle_initialize(id: Option<Value>, request: &JsonRpcRequest, peer: &ClientPeer) -> JsonRpcResponse {
     // Extract protocol version from client's request
     let client_protocol_version = request
         .params
@@ -133,15 +346,38 @@ fn handle_initialize(id: Option<Value>, request: &JsonRpcRequest) -> JsonRpcResp
         .and_then(|v| v.as_str())
         .unwrap_or("2024-11-05");
 
-    eprintln!("Client requested protocol version: {}", client_protocol_version);
+    // Later messages are shaped for the version both sides agreed on
+    let protocol_version = protocol_version::negotiate(client_protocol_version);
+    peer.set_protocol_version(protocol_version);
+    eprintln!(
+        "Client requested protocol version: {}, using {}",
+        client_protocol_version, protocol_version
+    );
+
+    // Clients with the roots capability can drive project selection, and
+    // downstream requests are only relayed for capabilities the client has
+    let capabilities = request
+        .params
+        .as_ref()
+        .and_then(|p| p.get("capabilities"))
+        .cloned()
+        .unwrap_or(json!({}));
+    peer.set_capabilities(capabilities);
 
-    // Use the client's protocol version in response
     let result = InitializeResult {
-        protocol_version: client_protocol_version.to_string(),
+        protocol_version: protocol_version.to_string(),
         capabilities: ServerCapabilities {
             tools: Some(ToolsCapability {
-                list_changed: None, // Empty tools capability
+                // Sent when client roots switch the active project or a
+                // background refresh finds different tools
+                list_changed: Some(true),
+            }),
+            // Relayed from the servers that send them
+            resources: Some(ResourcesCapability {
+                subscribe: Some(true),
+                list_changed: Some(true),
             }),
+            prompts: Some(PromptsCapability { list_changed: None }),
             experimental: None,
         },
         server_info: ServerInfo {
@@ -153,98 +389,265 @@ fn handle_initialize(id: Option<Value>, request: &JsonRpcRequest) -> JsonRpcResp
     JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
 }
 
-fn handle_tools_list(id: Option<Value>, storage: &Storage) -> JsonRpcResponse {
-    match storage.get_enabled_mcps_with_bindings() {
-        Ok(mcp_bindings) => {
-            let mut all_tools: Vec<McpTool> = Vec::new();
-
-            for (mcp, binding) in &mcp_bindings {
-                eprintln!("[handle_tools_list] Listing tools for MCP: {}", mcp.name);
-
-                // Merge env vars with overrides
-                let mut env_vars = mcp.config.env_vars.clone();
-                for override_var in &binding.overrides {
-                    if let Some(existing) = env_vars.iter_mut().find(|v| v.key == override_var.key) {
-                        existing.value = override_var.value.clone();
-                    } else {
-                        env_vars.push(override_var.clone());
-                    }
-                }
+/// Asks the client for its roots and re-scopes the router to the matching project
+async fn refresh_roots(router: Arc<Router>, peer: Arc<ClientPeer>) {
+    let result = match peer.request("roots/list", None).await {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("[refresh_roots] Failed to list client roots: {}", e);
+            return;
+        }
+    };
 
-                // Create MCP client to list actual tools
-                match mcp_client::McpClient::new(mcp, &env_vars) {
-                    Ok(client) => {
-                        // Initialize connection
-                        if let Err(e) = client.initialize() {
-                            eprintln!("[handle_tools_list] Failed to initialize MCP {}: {}", mcp.name, e);
-                            continue;
-                        }
-
-                        // List tools from this MCP
-                        match client.list_tools() {
-                            Ok(mcp_tools) => {
-                                eprintln!("[handle_tools_list] Found {} tools for MCP: {}", mcp_tools.len(), mcp.name);
-
-                                // Add each tool with server prefix
-                                for tool in mcp_tools {
-                                    let tool_name = match tool.get("name").and_then(|n| n.as_str()) {
-                                        Some(name) => name,
-                                        None => {
-                                            eprintln!("[handle_tools_list] Tool missing 'name' field, skipping");
-                                            continue;
-                                        }
-                                    };
-
-                                    // Create prefixed tool name: mcp_name__tool_name
-                                    // Replace spaces and special chars to match pattern ^[a-zA-Z0-9_-]{1,64}$
-                                    let mcp_prefix = mcp.name
-                                        .replace(" ", "_")
-                                        .replace("-", "_");
-                                    let prefixed_name = format!("{}__{}", mcp_prefix, tool_name);
-
-                                    // Extract description and schema
-                                    let description = tool
-                                        .get("description")
-                                        .and_then(|d| d.as_str())
-                                        .unwrap_or("")
-                                        .to_string();
-
-                                    let input_schema = tool
-                                        .get("inputSchema")
-                                        .cloned()
-                                        .unwrap_or(json!({}));
-
-                                    all_tools.push(McpTool {
-                                        name: prefixed_name,
-                                        description,
-                                        input_schema,
-                                    });
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("[handle_tools_list] Failed to list tools for MCP {}: {}", mcp.name, e);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("[handle_tools_list] Failed to create client for MCP {}: {}", mcp.name, e);
+    let roots: Vec<PathBuf> = result
+        .get("roots")
+        .and_then(|r| r.as_array())
+        .map(|roots| {
+            roots
+                .iter()
+                .filter_map(|root| root.get("uri").and_then(|u| u.as_str()))
+                .filter_map(projects::root_uri_to_path)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    eprintln!("[refresh_roots] Client roots: {:?}", roots);
+
+    match router.apply_roots(&roots) {
+        Ok(true) => peer.notify("notifications/tools/list_changed", None),
+        Ok(false) => {}
+        Err(e) => eprintln!("[refresh_roots] Failed to apply client roots: {}", e),
+    }
+}
+
+/// Lists a server's tools through the pool and stores them in the tool cache.
+/// Returns the tools and whether they differ from the previously cached set.
+async fn discover_tools(
+    router: Arc<Router>,
+    mcp: Mcp,
+    binding: ProjectMcpBinding,
+    env_vars: Vec<EnvVar>,
+) -> Result<(Vec<Value>, bool), String> {
+    let client = router.client(&mcp, &binding, &env_vars).await?;
+    let tools = client.list_tools().await?;
+    let changed = router
+        .tools
+        .update(&router.storage, &CacheKey::new(&mcp, &binding), tools.clone());
+    Ok((tools, changed))
+}
+
+/// Re-discovers a server's tools in the background and tells the client if they changed
+fn spawn_tools_refresh(
+    router: &Arc<Router>,
+    peer: &Arc<ClientPeer>,
+    mcp: Mcp,
+    binding: ProjectMcpBinding,
+    env_vars: Vec<EnvVar>,
+) {
+    let key = CacheKey::new(&mcp, &binding);
+    if !router.tools.begin_refresh(&key) {
+        return;
+    }
+
+    let router = router.clone();
+    let peer = peer.clone();
+    tokio::spawn(async move {
+        let name = mcp.name.clone();
+        match discover_tools(router.clone(), mcp, binding, env_vars).await {
+            Ok((_, true)) => {
+                eprintln!("[spawn_tools_refresh] Tools changed for MCP: {}", name);
+                peer.notify("notifications/tools/list_changed", None);
+            }
+            Ok((_, false)) => {}
+            Err(e) => eprintln!("[spawn_tools_refresh] Failed to refresh tools for MCP {}: {}", name, e),
+        }
+        router.tools.end_refresh(&key);
+    });
+}
+
+async fn handle_tools_list(
+    id: Option<Value>,
+    router: &Arc<Router>,
+    peer: &Arc<ClientPeer>,
+) -> JsonRpcResponse {
+    let mcp_bindings = match router.bindings() {
+        Ok(mcps) => mcps,
+        Err(e) => return JsonRpcResponse::error(id, -32000, format!("Failed to get MCPs: {}", e)),
+    };
+
+    let mut active_keys: HashSet<PoolKey> = HashSet::new();
+    let mut results: Vec<(Mcp, ProjectMcpBinding, Result<Vec<Value>, String>)> = Vec::new();
+    let mut tasks = Vec::new();
+
+    for (mcp, binding) in mcp_bindings {
+        let env_vars = router.resolve_env_vars(&mcp, &binding);
+        active_keys.insert(PoolKey::new(&router.session_id, &mcp, &binding, &env_vars));
+
+        // Serve from cache, refreshing stale entries in the background
+        if let Some((tools, stale)) = router.tools.get(&router.storage, &CacheKey::new(&mcp, &binding)) {
+            eprintln!("[handle_tools_list] Using cached tools for MCP: {}", mcp.name);
+            if stale {
+                spawn_tools_refresh(router, peer, mcp.clone(), binding.clone(), env_vars);
+            }
+            results.push((mcp, binding, Ok(tools)));
+            continue;
+        }
+
+        eprintln!("[handle_tools_list] Listing tools for MCP: {}", mcp.name);
+
+        // Spawned so a server that misses the deadline keeps starting in the
+        // background and is already pooled and cached for the next request
+        let handle = tokio::spawn(discover_tools(router.clone(), mcp.clone(), binding.clone(), env_vars));
+        tasks.push((mcp, binding, handle));
+    }
+
+    // Query every uncached server concurrently
+    let deadline = tokio::time::Instant::now() + DISCOVERY_TIMEOUT;
+    for (mcp, binding, mut handle) in tasks {
+        let result = match tokio::time::timeout_at(deadline, &mut handle).await {
+            Ok(Ok(result)) => result.map(|(tools, _)| tools),
+            Ok(Err(e)) => Err(format!("Discovery task failed: {}", e)),
+            Err(_) => {
+                // Let the client know once the late server has been cached
+                let peer = peer.clone();
+                tokio::spawn(async move {
+                    if let Ok(Ok(_)) = handle.await {
+                        peer.notify("notifications/tools/list_changed", None);
                     }
+                });
+                Err(format!("Timed out after {}s", DISCOVERY_TIMEOUT.as_secs()))
+            }
+        };
+        results.push((mcp, binding, result));
+    }
+
+    let mut listed_bindings: Vec<String> = Vec::new();
+    let mut name_requests: Vec<naming::ToolNameRequest> = Vec::new();
+    let mut listed_tools: Vec<(String, McpTool)> = Vec::new();
+    let mut failures: Vec<ServerFailure> = Vec::new();
+
+    for (mcp, binding, result) in results {
+        let mcp_tools = match result {
+            Ok(tools) => tools,
+            Err(e) => {
+                eprintln!("[handle_tools_list] Failed to list tools for MCP {}: {}", mcp.name, e);
+                failures.push(ServerFailure {
+                    mcp_id: mcp.id.clone(),
+                    mcp_name: mcp.name.clone(),
+                    error: e,
+                });
+                continue;
+            }
+        };
+
+        eprintln!("[handle_tools_list] Found {} tools for MCP: {}", mcp_tools.len(), mcp.name);
+        listed_bindings.push(binding.id.clone());
+
+        // Add each tool; names are assigned once every server's tools are known
+        for tool in mcp_tools {
+            let tool_name = match tool.get("name").and_then(|n| n.as_str()) {
+                Some(name) => name,
+                None => {
+                    eprintln!("[handle_tools_list] Tool missing 'name' field, skipping");
+                    continue;
                 }
+            };
+
+            if !tool_filter::is_tool_allowed(&binding.tool_settings, tool_name) {
+                continue;
             }
 
-            eprintln!("[handle_tools_list] Total tools listed: {}", all_tools.len());
-            let result = ListToolsResult { tools: all_tools };
-            JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
+            // Keep the rest of the server's definition
+            let mut mcp_tool: McpTool = match serde_json::from_value(tool.clone()) {
+                Ok(mcp_tool) => mcp_tool,
+                Err(e) => {
+                    eprintln!("[handle_tools_list] Skipping invalid tool {} from MCP {}: {}", tool_name, mcp.name, e);
+                    continue;
+                }
+            };
+            tool_args::advertised_schema(&binding.tool_settings, &mut mcp_tool.input_schema);
+
+            name_requests.push(naming::ToolNameRequest {
+                binding_id: binding.id.clone(),
+                prefix: naming::prefix(&mcp, &binding),
+                separator: naming::separator(&binding),
+                tool_name: tool_name.to_string(),
+                exposed_tool_name: tool_overrides::exposed_name(&binding.tool_settings, tool_name).to_string(),
+            });
+
+            tool_overrides::apply(&binding.tool_settings, tool_name, &mut mcp_tool);
+            listed_tools.push((mcp.id.clone(), mcp_tool));
         }
-        Err(e) => JsonRpcResponse::error(id, -32000, format!("Failed to get MCPs: {}", e)),
     }
+
+    // Name every tool prefix__tool, disambiguating collisions, and remember
+    // the names so calls can be mapped back
+    let assigned = naming::assign_tool_names(&name_requests);
+    let scope = router.scope();
+    let updated_at = chrono::Utc::now().to_rfc3339();
+    let mut all_tools: Vec<McpTool> = Vec::new();
+    let mut tool_names: Vec<ToolName> = Vec::new();
+
+    // Agents can manage the servers of the project they're working in
+    if router.project().is_some() {
+        all_tools.extend(toolkit::tools());
+    }
+
+    for ((request, (mcp_id, mut mcp_tool)), assigned) in name_requests.into_iter().zip(listed_tools).zip(assigned) {
+        if toolkit::builtin_name(&assigned.name).is_some() {
+            eprintln!(
+                "[handle_tools_list] Tool {} is hidden by the built-in tool of the same name",
+                assigned.name
+            );
+            continue;
+        }
+        if let Some(collision) = &assigned.collision {
+            eprintln!(
+                "[handle_tools_list] More than one tool is named {}, exposing {} as {}",
+                collision, request.tool_name, assigned.name
+            );
+        }
+        mcp_tool.name = assigned.name.clone();
+        all_tools.push(mcp_tool);
+        tool_names.push(ToolName {
+            scope: scope.clone(),
+            exposed_name: assigned.name,
+            binding_id: request.binding_id,
+            mcp_id,
+            tool_name: request.tool_name,
+            collision: assigned.collision,
+            updated_at: updated_at.clone(),
+        });
+    }
+
+    if let Err(e) = router.storage.save_tool_names(&scope, &listed_bindings, &tool_names) {
+        eprintln!("[handle_tools_list] Failed to save tool names: {}", e);
+    }
+
+    // Release processes for bindings that are gone or whose env changed
+    router.pool.retain(&router.session_id, active_keys);
+
+    protocol_version::adapt_tools(&peer.protocol_version(), &mut all_tools);
+
+    eprintln!(
+        "[handle_tools_list] Total tools listed: {} ({} server(s) failed)",
+        all_tools.len(),
+        failures.len()
+    );
+
+    // Report unreachable servers alongside the partial result
+    let result = ListToolsResult {
+        tools: all_tools,
+        meta: aggregate::failures_meta(failures),
+    };
+    JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
 }
 
 async fn handle_tools_call(
     id: Option<Value>,
     request: &JsonRpcRequest,
-    storage: &Storage,
-    secret_manager: &SecretManager,
+    router: &Router,
+    peer: &Arc<ClientPeer>,
 ) -> JsonRpcResponse {
     // Parse the call tool request
     let call_request: CallToolRequest = match request.params.as_ref() {
@@ -267,99 +670,150 @@ async fn handle_tools_call(
         }
     };
 
-    // Get all enabled MCPs with bindings
-    let mcp_bindings = match storage.get_enabled_mcps_with_bindings() {
-        Ok(mcps) => mcps,
-        Err(e) => {
+    let tool_name = &call_request.name;
+    if let Some(builtin) = toolkit::builtin_name(tool_name) {
+        let args = call_request.arguments.unwrap_or(json!({}));
+        let mut call_result = toolkit::call(router, peer, builtin, &args);
+        protocol_version::adapt_tool_result(&peer.protocol_version(), &mut call_result);
+        return JsonRpcResponse::success(id, serde_json::to_value(call_result).unwrap());
+    }
+
+    // Map the exposed name back to the binding and the server's tool name
+    let (mcp, binding, actual_tool_name) = match router.resolve_tool(tool_name) {
+        Ok(Some(t)) => t,
+        Ok(None) => {
             return JsonRpcResponse::error(
                 id,
-                -32000,
-                format!("Failed to get MCPs: {}", e),
+                -32602,
+                format!("Tool not found: {}", tool_name),
             )
         }
-    };
-
-    // Parse the prefixed tool name: "mcp_prefix__tool_name"
-    let tool_name = &call_request.name;
-    let (mcp_prefix, actual_tool_name) = match tool_name.split_once("__") {
-        Some((prefix, name)) => (prefix, name),
-        None => {
+        Err(e) => {
             return JsonRpcResponse::error(
                 id,
-                -32602,
-                format!("Invalid tool name format. Expected 'mcp_prefix__tool_name', got: {}", tool_name),
+                -32000,
+                format!("Failed to get MCPs: {}", e),
             )
         }
     };
+    let actual_tool_name = actual_tool_name.as_str();
 
-    // Find the MCP by matching the normalized prefix
-    let mut target_mcp: Option<(&Mcp, &ProjectMcpBinding)> = None;
-    for (mcp, binding) in &mcp_bindings {
-        let normalized_name = mcp.name
-            .replace(" ", "_")
-            .replace("-", "_");
-        if normalized_name == mcp_prefix {
-            target_mcp = Some((mcp, binding));
-            break;
-        }
+    if !tool_filter::is_tool_allowed(&binding.tool_settings, actual_tool_name) {
+        return JsonRpcResponse::error(
+            id,
+            -32602,
+            format!("Tool '{}' is not enabled for this project", tool_name),
+        );
     }
 
-    let (mcp, binding) = match target_mcp {
-        Some(t) => t,
-        None => {
-            return JsonRpcResponse::error(
-                id,
-                -32602,
-                format!("MCP not found for prefix: {}", mcp_prefix),
+    let env_vars = router.resolve_env_vars(&mcp, &binding);
+
+    // Execute the MCP with the actual tool name (without prefix)
+    let mut args = call_request.arguments.unwrap_or(json!({}));
+    let schema = router
+        .cached_tool(&mcp, &binding, actual_tool_name)
+        .and_then(|tool| tool.get("inputSchema").cloned());
+    tool_args::apply(&binding.tool_settings, schema.as_ref(), &mut args);
+    let on_progress = progress_relay(call_request.meta.as_ref(), peer);
+    let started = Instant::now();
+    // Reject malformed calls before a server is started for them
+    let validator = schema
+        .as_ref()
+        .and_then(|schema| router.tools.validator(&CacheKey::new(&mcp, &binding), actual_tool_name, schema));
+    let result = match validator.map(|validator| tool_args::validate(&validator, &args)) {
+        Some(Err(violations)) => Err(format!(
+            "Invalid arguments for {}:\n- {}",
+            tool_name,
+            violations.join("\n- ")
+        )),
+        _ => {
+            executor::execute_mcp(
+                router,
+                &mcp,
+                &binding,
+                &env_vars,
+                actual_tool_name,
+                &args,
+                on_progress,
             )
+            .await
         }
     };
+    call_log::record(
+        &router.storage,
+        &mcp,
+        &binding,
+        actual_tool_name,
+        &args,
+        &result,
+        started.elapsed(),
+    );
+
+    // Forward the server's result as is, including its own isError
+    let mut call_result = match result {
+        Ok(output) => serde_json::from_value(output).unwrap_or_else(|e| {
+            CallToolResult::error(format!("Error: {} returned an invalid tool result: {}", mcp.name, e))
+        }),
+        Err(e) => CallToolResult::error(format!("Error: {}", e)),
+    };
+    protocol_version::adapt_tool_result(&peer.protocol_version(), &mut call_result);
+    JsonRpcResponse::success(id, serde_json::to_value(call_result).unwrap())
+}
 
-    // Merge env vars with overrides
-    let mut env_vars = mcp.config.env_vars.clone();
-    for override_var in &binding.overrides {
-        if let Some(existing) = env_vars.iter_mut().find(|v| v.key == override_var.key) {
-            existing.value = override_var.value.clone();
-        } else {
-            env_vars.push(override_var.clone());
-        }
-    }
+/// Relays the downstream server's progress to the client under the client's
+/// own token, if it asked for progress
+fn progress_relay(meta: Option<&Value>, peer: &Arc<ClientPeer>) -> Option<ProgressHandler> {
+    let token = meta?.get("progressToken")?.clone();
+    let peer = peer.clone();
+    Some(Arc::new(move |mut params: Value| {
+        params["progressToken"] = token.clone();
+        peer.notify("notifications/progress", Some(params));
+    }))
+}
 
-    // Decrypt secrets
-    for env_var in env_vars.iter_mut() {
-        if env_var.is_secret {
-            if let Ok(Some(encrypted)) = storage.get_encrypted_secret(&env_var.value) {
-                if let Ok(decrypted) = secret_manager.decrypt(&encrypted) {
-                    env_var.value = decrypted;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initialized_with_roots_capability() {
+        let db_path = std::env::temp_dir().join(format!("mcp-toolkit-{}.db", uuid::Uuid::new_v4()));
+        let storage = Storage::open(&db_path).unwrap();
+        let router = Arc::new(Router::new(storage, SecretManager::new(&[0u8; 32]), None, false));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let input = [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+                "protocolVersion": protocol_version::LATEST_VERSION,
+                "capabilities": {"roots": {"listChanged": true}}
+            }}),
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}),
+        ]
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+        // Called outside the runtime, as serve_stdio is
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        serve_lines(&runtime, &router, input.as_bytes(), tx);
+
+        let sent: Vec<Value> = runtime.block_on(async {
+            let mut sent = Vec::new();
+            while let Ok(Some(message)) = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await {
+                sent.push(serde_json::from_str::<Value>(&message).unwrap());
+                let answered = sent.iter().any(|m| m["id"] == 2);
+                if answered && sent.iter().any(|m| m["method"] == "roots/list") {
+                    break;
                 }
             }
-        }
-    }
+            sent
+        });
 
-    // Execute the MCP with the actual tool name (without prefix)
-    let args = call_request.arguments.unwrap_or(json!({}));
-    let result = executor::execute_mcp(mcp, &env_vars, actual_tool_name, &args).await;
-
-    match result {
-        Ok(output) => {
-            let call_result = CallToolResult {
-                content: vec![ToolContent {
-                    content_type: "text".to_string(),
-                    text: serde_json::to_string_pretty(&output).unwrap_or_else(|_| output.to_string()),
-                }],
-                is_error: None,
-            };
-            JsonRpcResponse::success(id, serde_json::to_value(call_result).unwrap())
-        }
-        Err(e) => {
-            let call_result = CallToolResult {
-                content: vec![ToolContent {
-                    content_type: "text".to_string(),
-                    text: format!("Error: {}", e),
-                }],
-                is_error: Some(true),
-            };
-            JsonRpcResponse::success(id, serde_json::to_value(call_result).unwrap())
-        }
+        assert!(sent.iter().any(|m| m["id"] == 2 && m["result"] == json!({})));
+        assert!(sent.iter().any(|m| m["method"] == "roots/list"));
+
+        let _ = std::fs::remove_file(&db_path);
     }
 }
@@ -1,167 +1,267 @@
+use crate::http_transport::StreamableHttpTransport;
 use crate::models::*;
 use crate::mcp_protocol::*;
+use crate::protocol_version;
+use crate::sse_transport::SseTransport;
+use crate::stdio_transport::StdioTransport;
 use serde_json::{json, Value};
-use std::io::{BufRead, BufReader, Write as IoWrite};
-use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, Stdio};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// Deadline for protocol requests such as initialize and tools/list
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Deadline for tools/call, which may legitimately run for a while
+pub const TOOL_CALL_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Requests waiting for their response, keyed by the serialized JSON-RPC id
+type Pending = Arc<Mutex<HashMap<String, oneshot::Sender<JsonRpcResponse>>>>;
+
+/// Receives the params of each `notifications/progress` for one request
+pub type ProgressHandler = Arc<dyn Fn(Value) + Send + Sync>;
+
+/// Progress handlers by the serialized progress token we sent the server
+type ProgressHandlers = Arc<Mutex<HashMap<String, ProgressHandler>>>;
+
+/// Answers a request the server sent its client (see RELAYED_METHODS)
+pub type UpstreamHandler = Arc<
+    dyn Fn(String, Option<Value>) -> Pin<Box<dyn Future<Output = Result<Value, JsonRpcError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Server-to-client requests only the AI client can answer, so they are
+/// relayed to it rather than handled here
+pub const RELAYED_METHODS: [&str; 3] = ["sampling/createMessage", "elicitation/create", "roots/list"];
+
+/// Receives the server's notifications listed in RELAYED_NOTIFICATIONS
+pub type NotificationHandler = Arc<dyn Fn(String, Option<Value>) + Send + Sync>;
+
+/// Server notifications the AI client needs to see, relayed to it as they arrive
+pub const RELAYED_NOTIFICATIONS: [&str; 2] = [
+    "notifications/resources/updated",
+    "notifications/resources/list_changed",
+];
+
+/// How messages reach the downstream server. Everything the server sends
+/// back arrives on the channel given to the transport when it was created.
+pub enum Transport {
+    Stdio(StdioTransport),
+    StreamableHttp(StreamableHttpTransport),
+    Sse(SseTransport),
+}
+
+impl Transport {
+    async fn send(&self, message: &Value) -> Result<(), String> {
+        match self {
+            Transport::Stdio(t) => t.send(message).await,
+            Transport::StreamableHttp(t) => t.send(message).await,
+            Transport::Sse(t) => t.send(message).await,
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        match self {
+            Transport::Stdio(t) => t.is_alive(),
+            Transport::StreamableHttp(t) => t.is_alive(),
+            Transport::Sse(t) => t.is_alive(),
+        }
+    }
+
+    fn shutdown(&self) -> Result<(), String> {
+        match self {
+            Transport::Stdio(t) => t.shutdown(),
+            Transport::StreamableHttp(t) => t.shutdown(),
+            Transport::Sse(t) => t.shutdown(),
+        }
+    }
+}
 
+/// A connection to one downstream MCP server. Responses are matched to
+/// requests by id, so any number of requests can be in flight at once.
 pub struct McpClient {
-    process: Arc<Mutex<Child>>,
-    stdin: Arc<Mutex<ChildStdin>>,
-    stdout: Arc<Mutex<BufReader<ChildStdout>>>,
-    next_id: Arc<AtomicU64>,
+    transport: Arc<Transport>,
+    next_id: AtomicU64,
+    pending: Pending,
+    progress: ProgressHandlers,
+    /// Where relayed requests go: the AI client that last used this server
+    upstream: Arc<Mutex<Option<UpstreamHandler>>>,
+    /// Where relayed notifications go, like `upstream`
+    notifier: Arc<Mutex<Option<NotificationHandler>>>,
+    /// Capabilities the server declared in its initialize response
+    capabilities: Mutex<Value>,
     pub mcp_name: String,
 }
 
 impl McpClient {
-    /// Creates a new MCP client and starts the process
-    pub fn new(mcp: &Mcp, env_vars: &[EnvVar]) -> Result<Self, String> {
+    /// Starts the server (or opens the HTTP session) and initializes it
+    pub async fn connect(mcp: &Mcp, env_vars: &[EnvVar]) -> Result<Self, String> {
         eprintln!("[McpClient] Starting MCP: {}", mcp.name);
 
-        let mut cmd = match &mcp.mcp_type {
-            McpType::Docker => {
-                let image = mcp
-                    .config
-                    .docker_image
-                    .as_ref()
-                    .ok_or("No docker image specified")?;
-
-                let mut cmd = Command::new("docker");
-                cmd.arg("run")
-                    .arg("--rm")
-                    .arg("-i") // Interactive mode for stdin
-                    .arg("--init"); // Use init process
-
-                // Add environment variables
-                for env_var in env_vars {
-                    cmd.arg("-e").arg(format!("{}={}", env_var.key, env_var.value));
-                }
-
-                cmd.arg(image);
-                cmd
+        let (tx, rx) = mpsc::unbounded_channel();
+        let transport = match &mcp.mcp_type {
+            McpType::Docker | McpType::Binary => {
+                Transport::Stdio(StdioTransport::spawn(mcp, env_vars, tx)?)
             }
-            McpType::Binary => {
-                let binary_path = mcp
-                    .config
-                    .binary_path
-                    .as_ref()
-                    .ok_or("No binary path specified")?;
-
-                let mut cmd = Command::new(binary_path);
-
-                // Add environment variables
-                for env_var in env_vars {
-                    cmd.env(&env_var.key, &env_var.value);
+            McpType::Http => match mcp.config.http_transport {
+                HttpTransport::Sse => {
+                    Transport::Sse(SseTransport::connect(mcp, env_vars, tx).await?)
                 }
-
-                // Add arguments if specified
-                if !mcp.config.args.is_empty() {
-                    cmd.args(&mcp.config.args);
+                HttpTransport::Auto | HttpTransport::StreamableHttp => {
+                    Transport::StreamableHttp(StreamableHttpTransport::new(mcp, env_vars, tx)?)
                 }
-
-                cmd
-            }
-            McpType::Http => {
-                return Err("HTTP MCPs not supported via stdio client".to_string());
-            }
+            },
         };
 
-        // Configure stdio
-        cmd.stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        // Start the process
-        let mut process = cmd.spawn().map_err(|e| {
-            format!("Failed to start MCP process '{}': {}", mcp.name, e)
-        })?;
-
-        // Take ownership of stdin/stdout/stderr
-        let stdin = process
-            .stdin
-            .take()
-            .ok_or("Failed to open stdin for MCP process")?;
-        let stdout = process
-            .stdout
-            .take()
-            .ok_or("Failed to open stdout for MCP process")?;
-        let stderr = process
-            .stderr
-            .take()
-            .ok_or("Failed to open stderr for MCP process")?;
-
-        // Spawn thread to read stderr
-        let mcp_name_clone = mcp.name.clone();
-        thread::spawn(move || {
-            let reader = BufReader::new(stderr);
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    eprintln!("[McpClient stderr:{}] {}", mcp_name_clone, line);
-                }
+        let client = Self::start(mcp, transport, rx);
+        match client.initialize().await {
+            Ok(_) => Ok(client),
+            // Older servers answer the Streamable HTTP initialize POST with 4xx
+            Err(e)
+                if mcp.config.http_transport == HttpTransport::Auto
+                    && client.rejected_initialize() =>
+            {
+                eprintln!(
+                    "[McpClient] {} rejected Streamable HTTP ({}), falling back to HTTP+SSE",
+                    mcp.name, e
+                );
+                let (tx, rx) = mpsc::unbounded_channel();
+                let transport = Transport::Sse(SseTransport::connect(mcp, env_vars, tx).await?);
+                let client = Self::start(mcp, transport, rx);
+                client.initialize().await?;
+                Ok(client)
             }
-        });
+            Err(e) => Err(e),
+        }
+    }
 
-        let client = McpClient {
-            process: Arc::new(Mutex::new(process)),
-            stdin: Arc::new(Mutex::new(stdin)),
-            stdout: Arc::new(Mutex::new(BufReader::new(stdout))),
-            next_id: Arc::new(AtomicU64::new(1)),
+    /// Wraps a transport and starts dispatching the messages it receives
+    fn start(mcp: &Mcp, transport: Transport, incoming: mpsc::UnboundedReceiver<Value>) -> Self {
+        let transport = Arc::new(transport);
+        let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+        let progress: ProgressHandlers = Arc::new(Mutex::new(HashMap::new()));
+        let upstream = Arc::new(Mutex::new(None));
+        let notifier = Arc::new(Mutex::new(None));
+
+        tokio::spawn(dispatch_incoming(
+            incoming,
+            pending.clone(),
+            progress.clone(),
+            upstream.clone(),
+            notifier.clone(),
+            Arc::downgrade(&transport),
+            mcp.name.clone(),
+        ));
+
+        McpClient {
+            transport,
+            next_id: AtomicU64::new(1),
+            pending,
+            progress,
+            upstream,
+            notifier,
+            capabilities: Mutex::new(json!({})),
             mcp_name: mcp.name.clone(),
-        };
+        }
+    }
 
-        eprintln!("[McpClient] Process started for: {}", mcp.name);
-        Ok(client)
+    fn rejected_initialize(&self) -> bool {
+        match self.transport.as_ref() {
+            Transport::StreamableHttp(t) => t.rejected_initialize(),
+            _ => false,
+        }
     }
 
     /// Initialize the MCP connection
-    pub fn initialize(&self) -> Result<Value, String> {
+    async fn initialize(&self) -> Result<Value, String> {
         eprintln!("[McpClient] Initializing: {}", self.mcp_name);
 
-        let init_request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: Some(json!(self.next_id.fetch_add(1, Ordering::SeqCst))),
-            method: "initialize".to_string(),
-            params: Some(json!({
-                "protocolVersion": "2025-06-18",
-                "capabilities": {},
-                "clientInfo": {
-                    "name": "mcp-toolkit",
-                    "version": "0.1.0"
-                }
-            })),
-        };
+        let params = json!({
+            "protocolVersion": protocol_version::LATEST_VERSION,
+            // Relayed to the AI client, which may turn them down
+            "capabilities": {
+                "sampling": {},
+                "elicitation": {},
+                "roots": {}
+            },
+            "clientInfo": {
+                "name": "mcp-toolkit",
+                "version": "0.1.0"
+            }
+        });
 
-        let response = self.send_request(&init_request)?;
+        let response = self.send_request("initialize", params, REQUEST_TIMEOUT).await?;
         eprintln!("[McpClient] Initialize response: {:?}", response);
 
-        // Send initialized notification
-        let init_notification = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: None,
-            method: "notifications/initialized".to_string(),
-            params: None,
-        };
+        // The server answers with our version or an older one it prefers;
+        // one we don't speak means there's nothing to agree on
+        let version = response
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| format!("{} did not send a protocol version", self.mcp_name))?;
+        if !protocol_version::is_supported(version) {
+            return Err(format!(
+                "{} requires unsupported protocol version {} (supported: {})",
+                self.mcp_name,
+                version,
+                protocol_version::SUPPORTED_VERSIONS.join(", ")
+            ));
+        }
+        eprintln!("[McpClient] Using protocol version {} with: {}", version, self.mcp_name);
 
-        self.send_notification(&init_notification)?;
+        if let Some(capabilities) = response.get("capabilities") {
+            *self.capabilities.lock().unwrap() = capabilities.clone();
+        }
+        if let Transport::StreamableHttp(http) = self.transport.as_ref() {
+            http.set_protocol_version(version);
+        }
+
+        // Send initialized notification
+        self.notify("notifications/initialized", None).await?;
         eprintln!("[McpClient] Sent initialized notification for: {}", self.mcp_name);
 
+        // Messages the server initiates arrive on a separate stream
+        if let Transport::StreamableHttp(http) = self.transport.as_ref() {
+            http.listen();
+        }
+
         Ok(response)
     }
 
+    /// Routes the server's sampling, elicitation and roots requests to `handler`
+    pub fn set_upstream(&self, handler: UpstreamHandler) {
+        *self.upstream.lock().unwrap() = Some(handler);
+    }
+
+    /// Routes the server's resource notifications to `handler`
+    pub fn set_notifier(&self, handler: NotificationHandler) {
+        *self.notifier.lock().unwrap() = Some(handler);
+    }
+
+    /// A capability (e.g. "resources") the server declared during initialize
+    pub fn capability(&self, name: &str) -> Option<Value> {
+        self.capabilities.lock().unwrap().get(name).cloned()
+    }
+
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capability(capability).is_some()
+    }
+
+    /// Send an arbitrary request to the MCP server and return its result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        self.send_request(method, params, REQUEST_TIMEOUT).await
+    }
+
     /// List available tools from the MCP server
-    pub fn list_tools(&self) -> Result<Vec<Value>, String> {
+    pub async fn list_tools(&self) -> Result<Vec<Value>, String> {
         eprintln!("[McpClient] Listing tools for: {}", self.mcp_name);
 
-        let list_request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: Some(json!(self.next_id.fetch_add(1, Ordering::SeqCst))),
-            method: "tools/list".to_string(),
-            params: Some(json!({})),
-        };
-
-        let response = self.send_request(&list_request)?;
+        let response = self.send_request("tools/list", json!({}), REQUEST_TIMEOUT).await?;
 
         // Extract tools array from response
         let tools = response
@@ -174,82 +274,95 @@ impl McpClient {
         Ok(tools)
     }
 
-    /// Call a tool on the MCP server
-    pub fn call_tool(&self, tool_name: &str, arguments: &Value) -> Result<Value, String> {
+    /// Call a tool on the MCP server. `on_progress` receives the server's
+    /// progress notifications for this call.
+    pub async fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        on_progress: Option<ProgressHandler>,
+    ) -> Result<Value, String> {
         eprintln!(
             "[McpClient] Calling tool '{}' on: {}",
             tool_name, self.mcp_name
         );
 
-        let call_request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: Some(json!(self.next_id.fetch_add(1, Ordering::SeqCst))),
-            method: "tools/call".to_string(),
-            params: Some(json!({
-                "name": tool_name,
-                "arguments": arguments
-            })),
-        };
+        let mut params = json!({
+            "name": tool_name,
+            "arguments": arguments
+        });
 
-        let response = self.send_request(&call_request)?;
+        // Our own token, since several clients may share this server
+        let _progress = on_progress.map(|handler| {
+            let token = json!(format!("mcp-toolkit-{}", self.next_id.fetch_add(1, Ordering::SeqCst)));
+            params["_meta"] = json!({ "progressToken": token });
+            let key = token.to_string();
+            self.progress.lock().unwrap().insert(key.clone(), handler);
+            ProgressRegistration {
+                progress: &self.progress,
+                key,
+            }
+        });
+
+        let response = self.send_request("tools/call", params, TOOL_CALL_TIMEOUT).await?;
         eprintln!("[McpClient] Tool call response: {:?}", response);
 
         Ok(response)
     }
 
-    /// Send a JSON-RPC request and wait for response
-    fn send_request(&self, request: &JsonRpcRequest) -> Result<Value, String> {
-        // Serialize request
-        let request_str =
-            serde_json::to_string(request).map_err(|e| format!("Failed to serialize request: {}", e))?;
-
-        eprintln!("[McpClient] >>> {}", request_str);
-
-        // Send to stdin
-        {
-            let mut stdin = self.stdin.lock().unwrap();
-            writeln!(stdin, "{}", request_str).map_err(|e| format!("Failed to write to stdin: {}", e))?;
-            stdin.flush().map_err(|e| format!("Failed to flush stdin: {}", e))?;
-        }
-
-        // Read response from stdout - keep reading until we get valid JSON
-        let response_str = {
-            let mut stdout = self.stdout.lock().unwrap();
-            let mut attempts = 0;
-            const MAX_ATTEMPTS: i32 = 10;
-
-            loop {
-                let mut line = String::new();
-                let bytes_read = stdout
-                    .read_line(&mut line)
-                    .map_err(|e| format!("Failed to read from stdout: {}", e))?;
-
-                if bytes_read == 0 {
-                    return Err("EOF: Process closed stdout".to_string());
-                }
-
-                let trimmed = line.trim();
-
-                // Skip empty lines or lines that don't look like JSON
-                if trimmed.is_empty() || !trimmed.starts_with('{') {
-                    eprintln!("[McpClient] Skipping non-JSON line: {}", trimmed);
-                    attempts += 1;
-                    if attempts >= MAX_ATTEMPTS {
-                        return Err("Too many non-JSON lines, giving up".to_string());
-                    }
-                    continue;
-                }
+    /// Send a JSON-RPC request and wait for its response, giving up after `timeout`
+    async fn send_request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(id)),
+            method: method.to_string(),
+            params: Some(params),
+        };
+        let message = serde_json::to_value(&request)
+            .map_err(|e| format!("Failed to serialize request: {}", e))?;
+
+        let (tx, rx) = oneshot::channel();
+        let key = json!(id).to_string();
+        self.pending.lock().unwrap().insert(key.clone(), tx);
+
+        // Forgets the request however this future ends, including being
+        // dropped because the AI client cancelled its own request
+        let mut in_flight = InFlight {
+            client: self,
+            id,
+            key,
+            outcome: Outcome::Cancelled,
+        };
 
-                break line;
+        // The deadline covers the send too: a Streamable HTTP server may hold
+        // the POST open until it has the reply
+        let result = tokio::time::timeout(timeout, async {
+            self.transport.send(&message).await?;
+            rx.await.map_err(|_| {
+                format!(
+                    "Connection to {} closed while waiting for '{}'",
+                    self.mcp_name, method
+                )
+            })
+        })
+        .await;
+        in_flight.outcome = match result {
+            Err(_) => Outcome::TimedOut,
+            Ok(_) => Outcome::Finished,
+        };
+        let response = match result {
+            Ok(response) => response?,
+            Err(_) => {
+                return Err(format!(
+                    "Timed out after {}s waiting for '{}' from {}",
+                    timeout.as_secs(),
+                    method,
+                    self.mcp_name
+                ));
             }
         };
 
-        eprintln!("[McpClient] <<< {}", response_str.trim());
-
-        // Parse response
-        let response: JsonRpcResponse = serde_json::from_str(&response_str)
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
-
         // Check for error
         if let Some(error) = response.error {
             return Err(format!(
@@ -265,41 +378,28 @@ impl McpClient {
     }
 
     /// Send a notification (no response expected)
-    fn send_notification(&self, request: &JsonRpcRequest) -> Result<(), String> {
-        let request_str =
-            serde_json::to_string(request).map_err(|e| format!("Failed to serialize notification: {}", e))?;
-
-        eprintln!("[McpClient] >>> (notification) {}", request_str);
-
-        let mut stdin = self.stdin.lock().unwrap();
-        writeln!(stdin, "{}", request_str).map_err(|e| format!("Failed to write notification: {}", e))?;
-        stdin.flush().map_err(|e| format!("Failed to flush stdin: {}", e))?;
-
-        Ok(())
+    async fn notify(&self, method: &str, params: Option<Value>) -> Result<(), String> {
+        tokio::time::timeout(REQUEST_TIMEOUT, self.transport.send(&notification(method, params)))
+            .await
+            .map_err(|_| format!("Timed out sending '{}' to {}", method, self.mcp_name))?
     }
 
-    /// Check if the process is still running
+    /// Check if the server can still be used
     pub fn is_alive(&self) -> bool {
-        let mut process = self.process.lock().unwrap();
-        match process.try_wait() {
-            Ok(Some(_)) => false, // Process has exited
-            Ok(None) => true,     // Process is still running
-            Err(_) => false,      // Error checking status
+        self.transport.is_alive()
+    }
+
+    /// Ends an HTTP session and waits for the server to acknowledge it
+    pub async fn close(&self) {
+        if let Transport::StreamableHttp(http) = self.transport.as_ref() {
+            http.close().await;
         }
     }
 
     /// Shutdown the MCP client
     pub fn shutdown(&self) -> Result<(), String> {
         eprintln!("[McpClient] Shutting down: {}", self.mcp_name);
-
-        let mut process = self.process.lock().unwrap();
-        process
-            .kill()
-            .map_err(|e| format!("Failed to kill process: {}", e))?;
-        process
-            .wait()
-            .map_err(|e| format!("Failed to wait for process: {}", e))?;
-
+        self.transport.shutdown()?;
         eprintln!("[McpClient] Shutdown complete: {}", self.mcp_name);
         Ok(())
     }
@@ -311,3 +411,226 @@ impl Drop for McpClient {
         let _ = self.shutdown();
     }
 }
+
+fn notification(method: &str, params: Option<Value>) -> Value {
+    let mut message = json!({ "jsonrpc": "2.0", "method": method });
+    if let Some(params) = params {
+        message["params"] = params;
+    }
+    message
+}
+
+enum Outcome {
+    Finished,
+    TimedOut,
+    /// The caller stopped waiting before the response arrived
+    Cancelled,
+}
+
+/// A request waiting for its response. Unless it finished, the server is
+/// told to stop working on it once it is dropped.
+struct InFlight<'a> {
+    client: &'a McpClient,
+    id: u64,
+    key: String,
+    outcome: Outcome,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.client.pending.lock().unwrap().remove(&self.key);
+
+        let reason = match self.outcome {
+            Outcome::Finished => return,
+            Outcome::TimedOut => "Request timed out",
+            Outcome::Cancelled => "Request cancelled by the client",
+        };
+        eprintln!(
+            "[McpClient] Cancelling request {} on {}: {}",
+            self.id, self.client.mcp_name, reason
+        );
+
+        let message = notification(
+            "notifications/cancelled",
+            Some(json!({ "requestId": self.id, "reason": reason })),
+        );
+        let transport = self.client.transport.clone();
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                let _ = transport.send(&message).await;
+            });
+        }
+    }
+}
+
+struct ProgressRegistration<'a> {
+    progress: &'a ProgressHandlers,
+    key: String,
+}
+
+impl Drop for ProgressRegistration<'_> {
+    fn drop(&mut self) {
+        self.progress.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// Background task routing each message from the server: responses go to the
+/// request waiting for them, progress to the call it belongs to, server
+/// requests are answered or relayed upstream, and resource notifications relayed
+async fn dispatch_incoming(
+    mut incoming: mpsc::UnboundedReceiver<Value>,
+    pending: Pending,
+    progress: ProgressHandlers,
+    upstream: Arc<Mutex<Option<UpstreamHandler>>>,
+    notifier: Arc<Mutex<Option<NotificationHandler>>>,
+    transport: Weak<Transport>,
+    mcp_name: String,
+) {
+    while let Some(message) = incoming.recv().await {
+        let method = message.get("method").and_then(|m| m.as_str());
+        match (method, message.get("id")) {
+            (None, Some(id)) => {
+                let key = id.to_string();
+                let Some(tx) = pending.lock().unwrap().remove(&key) else {
+                    eprintln!(
+                        "[McpClient] Dropping response {} from {}: nothing is waiting for it",
+                        key, mcp_name
+                    );
+                    continue;
+                };
+                match serde_json::from_value::<JsonRpcResponse>(message) {
+                    Ok(response) => {
+                        let _ = tx.send(response);
+                    }
+                    Err(e) => eprintln!("[McpClient] Invalid response from {}: {}", mcp_name, e),
+                }
+            }
+            (Some(method), Some(id)) => {
+                let Some(transport) = transport.upgrade() else {
+                    break;
+                };
+                let upstream = upstream.lock().unwrap().clone();
+                let reply = answer_server_request(
+                    method.to_string(),
+                    id.clone(),
+                    message.get("params").cloned(),
+                    upstream,
+                    mcp_name.clone(),
+                );
+                // Relayed requests may wait on the user, so don't hold up other responses
+                tokio::spawn(async move {
+                    let reply = reply.await;
+                    if let Err(e) = transport.send(&reply).await {
+                        eprintln!("[McpClient] Failed to answer server request: {}", e);
+                    }
+                });
+            }
+            (Some("notifications/progress"), None) => {
+                let Some(params) = message.get("params") else {
+                    continue;
+                };
+                let key = params.get("progressToken").map(|t| t.to_string()).unwrap_or_default();
+                // Cloned so the handler runs without holding the lock
+                let handler = progress.lock().unwrap().get(&key).cloned();
+                match handler {
+                    Some(handler) => handler(params.clone()),
+                    None => eprintln!("[McpClient] Dropping progress {} from {}: no call is waiting", key, mcp_name),
+                }
+            }
+            (Some(method), None) if RELAYED_NOTIFICATIONS.contains(&method) => {
+                let handler = notifier.lock().unwrap().clone();
+                match handler {
+                    Some(handler) => handler(method.to_string(), message.get("params").cloned()),
+                    None => eprintln!("[McpClient] Dropping {} from {}: no client to relay it to", method, mcp_name),
+                }
+            }
+            (Some(method), None) => {
+                eprintln!("[McpClient] Notification from {}: {}", mcp_name, method);
+            }
+            (None, None) => {
+                eprintln!("[McpClient] Ignoring invalid message from {}: {}", mcp_name, message);
+            }
+        }
+    }
+
+    eprintln!("[McpClient] Connection closed: {}", mcp_name);
+    // Dropping the senders fails every request still waiting
+    pending.lock().unwrap().clear();
+}
+
+/// Reply to a request the server sent us, under the server's own id
+async fn answer_server_request(
+    method: String,
+    id: Value,
+    params: Option<Value>,
+    upstream: Option<UpstreamHandler>,
+    mcp_name: String,
+) -> Value {
+    let result = match (method.as_str(), upstream) {
+        ("ping", _) => Ok(json!({})),
+        (method, Some(upstream)) if RELAYED_METHODS.contains(&method) => {
+            eprintln!("[McpClient] Relaying {} from {} to the client", method, mcp_name);
+            upstream(method.to_string(), params).await
+        }
+        (method, _) => {
+            eprintln!("[McpClient] Unsupported request from {}: {}", mcp_name, method);
+            Err(JsonRpcError {
+                code: -32601,
+                message: format!("Method not found: {}", method),
+                data: None,
+            })
+        }
+    };
+
+    let response = match result {
+        Ok(result) => JsonRpcResponse::success(Some(id), result),
+        Err(error) => JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id),
+            result: None,
+            error: Some(error),
+        },
+    };
+    serde_json::to_value(response).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_responses_reach_their_own_request() {
+        let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+        let progress: ProgressHandlers = Arc::new(Mutex::new(HashMap::new()));
+        let (first_tx, first) = oneshot::channel();
+        let (second_tx, second) = oneshot::channel();
+        pending.lock().unwrap().insert(json!(1).to_string(), first_tx);
+        pending.lock().unwrap().insert(json!(2).to_string(), second_tx);
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let handler: ProgressHandler = {
+            let reported = reported.clone();
+            Arc::new(move |params| reported.lock().unwrap().push(params))
+        };
+        progress.lock().unwrap().insert(json!("t1").to_string(), handler);
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        // A notification first, then the responses in reverse order
+        let progress_params = json!({ "progressToken": "t1", "progress": 1 });
+        tx.send(json!({"jsonrpc": "2.0", "method": "notifications/progress", "params": progress_params}))
+            .unwrap();
+        tx.send(json!({"jsonrpc": "2.0", "id": 2, "result": {"n": 2}})).unwrap();
+        tx.send(json!({"jsonrpc": "2.0", "id": 1, "result": {"n": 1}})).unwrap();
+        drop(tx);
+
+        let upstream = Arc::new(Mutex::new(None));
+        let notifier = Arc::new(Mutex::new(None));
+        let mcp_name = "test".to_string();
+        dispatch_incoming(rx, pending.clone(), progress, upstream, notifier, Weak::new(), mcp_name).await;
+
+        assert_eq!(first.await.unwrap().result, Some(json!({"n": 1})));
+        assert_eq!(second.await.unwrap().result, Some(json!({"n": 2})));
+        assert_eq!(reported.lock().unwrap()[0]["progress"], 1);
+        assert!(pending.lock().unwrap().is_empty());
+    }
+}
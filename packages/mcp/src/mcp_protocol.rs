@@ -4,9 +4,11 @@ use serde_json::Value as JsonValue;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JsonRpcRequest {
     pub jsonrpc: String,
+    /// Absent for notifications
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<JsonValue>,
     pub method: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub params: Option<JsonValue>,
 }
 
@@ -66,6 +68,10 @@ pub struct ServerCapabilities {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<ToolsCapability>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourcesCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<PromptsCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub experimental: Option<JsonValue>,
 }
 
@@ -76,6 +82,22 @@ pub struct ToolsCapability {
     pub list_changed: Option<bool>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResourcesCapability {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subscribe: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "listChanged")]
+    pub list_changed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromptsCapability {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "listChanged")]
+    pub list_changed: Option<bool>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerInfo {
     pub name: String,
@@ -85,14 +107,71 @@ pub struct ServerInfo {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct McpTool {
     pub name: String,
-    pub description: String,
-    #[serde(rename = "inputSchema")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "inputSchema", default = "empty_object")]
     pub input_schema: JsonValue,
+    #[serde(rename = "outputSchema")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<JsonValue>,
+    /// Hints such as `readOnlyHint` and `destructiveHint` that clients use for approval
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<JsonValue>,
+    #[serde(rename = "_meta")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonValue>,
+    /// Fields from newer protocol versions, forwarded as is
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, JsonValue>,
+}
+
+fn empty_object() -> JsonValue {
+    JsonValue::Object(Default::default())
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListToolsResult {
     pub tools: Vec<McpTool>,
+    #[serde(rename = "_meta")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListResourcesResult {
+    pub resources: Vec<JsonValue>,
+    #[serde(rename = "_meta")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListResourceTemplatesResult {
+    #[serde(rename = "resourceTemplates")]
+    pub resource_templates: Vec<JsonValue>,
+    #[serde(rename = "_meta")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListPromptsResult {
+    pub prompts: Vec<JsonValue>,
+    #[serde(rename = "_meta")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonValue>,
+}
+
+/// A downstream server that could not be queried while aggregating a result
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerFailure {
+    #[serde(rename = "mcpId")]
+    pub mcp_id: String,
+    #[serde(rename = "mcpName")]
+    pub mcp_name: String,
+    pub error: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -100,19 +179,128 @@ pub struct CallToolRequest {
     pub name: String,
     #[serde(default)]
     pub arguments: Option<JsonValue>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonValue>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CallToolResult {
     pub content: Vec<ToolContent>,
+    #[serde(rename = "structuredContent")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<JsonValue>,
     #[serde(rename = "isError")]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    #[serde(rename = "_meta")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonValue>,
+}
+
+impl CallToolResult {
+    /// A failed call reported to the client as a tool result rather than a protocol error
+    pub fn error(message: String) -> Self {
+        CallToolResult {
+            content: vec![ToolContent::Text {
+                text: message,
+                annotations: None,
+                meta: None,
+            }],
+            structured_content: None,
+            is_error: Some(true),
+            meta: None,
+        }
+    }
 }
 
+/// One block of a tool result: the MCP content union
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ToolContent {
-    #[serde(rename = "type")]
-    pub content_type: String,
-    pub text: String,
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolContent {
+    Text {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<JsonValue>,
+        #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+        meta: Option<JsonValue>,
+    },
+    Image {
+        /// Base64-encoded
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<JsonValue>,
+        #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+        meta: Option<JsonValue>,
+    },
+    Audio {
+        /// Base64-encoded
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<JsonValue>,
+        #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+        meta: Option<JsonValue>,
+    },
+    /// A resource the client can read or subscribe to, left on the server
+    ResourceLink {
+        uri: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(rename = "mimeType", default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        size: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<JsonValue>,
+        #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+        meta: Option<JsonValue>,
+    },
+    /// An embedded resource: `{uri, mimeType?, text}` or `{uri, mimeType?, blob}`
+    Resource {
+        resource: JsonValue,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<JsonValue>,
+        #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+        meta: Option<JsonValue>,
+    },
+    /// A content type from a newer protocol version, forwarded as is
+    #[serde(untagged)]
+    Other(JsonValue),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_fields_round_trip() {
+        let tool = json!({
+            "name": "search",
+            "inputSchema": {"type": "object"},
+            "icons": [{"src": "https://example.com/icon.png"}]
+        });
+        let parsed: McpTool = serde_json::from_value(tool.clone()).unwrap();
+        assert_eq!(parsed.description, None);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), tool);
+    }
+
+    #[test]
+    fn test_unknown_content_is_forwarded() {
+        let output = json!({
+            "content": [
+                {"type": "text", "text": "hi"},
+                {"type": "video", "uri": "file:///a.mp4", "mimeType": "video/mp4"}
+            ]
+        });
+        let result: CallToolResult = serde_json::from_value(output.clone()).unwrap();
+        assert!(matches!(result.content[1], ToolContent::Other(_)));
+        assert_eq!(serde_json::to_value(&result).unwrap(), output);
+    }
 }
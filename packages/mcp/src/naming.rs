@@ -0,0 +1,246 @@
+use crate::models::*;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+/// Separator between the server prefix and the downstream tool or prompt
+/// name, unless the binding sets its own
+pub const DEFAULT_SEPARATOR: &str = "__";
+
+/// Scheme of resource URIs exposed by the router: `mcp-toolkit://<prefix>/<original uri>`
+const URI_SCHEME: &str = "mcp-toolkit://";
+
+/// Clients reject tool names that don't match ^[a-zA-Z0-9_-]{1,64}$
+const MAX_NAME_LEN: usize = 64;
+
+/// Hex digits of the hash that disambiguates shortened or colliding names
+const HASH_LEN: usize = 6;
+
+/// Prefix identifying a binding's server in exposed names: the binding's own
+/// prefix, or the MCP name with spaces, dashes and other invalid characters
+/// replaced by `_`
+pub fn prefix(mcp: &Mcp, binding: &ProjectMcpBinding) -> String {
+    match binding.tool_settings.prefix.as_deref().filter(|p| !p.is_empty()) {
+        Some(prefix) => sanitize(prefix),
+        None => sanitize(&mcp.name.replace('-', "_")),
+    }
+}
+
+/// Separator between the prefix and the tool or prompt name, with invalid
+/// characters replaced like the prefix's
+pub fn separator(binding: &ProjectMcpBinding) -> String {
+    match binding.tool_settings.separator.as_deref().filter(|s| !s.is_empty()) {
+        Some(separator) => sanitize(separator),
+        None => DEFAULT_SEPARATOR.to_string(),
+    }
+}
+
+/// Builds the exposed name `prefix<separator>name`
+pub fn prefixed_name(mcp: &Mcp, binding: &ProjectMcpBinding, name: &str) -> String {
+    format!("{}{}{}", prefix(mcp, binding), separator(binding), name)
+}
+
+/// Finds the binding whose prefix and separator start `name` and returns the
+/// rest; the longest match wins, so `a_b__x` goes to `a_b` rather than `a`
+pub fn split_prefixed<'a>(
+    bindings: &'a [(Mcp, ProjectMcpBinding)],
+    name: &'a str,
+) -> Option<(&'a (Mcp, ProjectMcpBinding), &'a str)> {
+    bindings
+        .iter()
+        .filter_map(|entry| {
+            let (mcp, binding) = entry;
+            let head = format!("{}{}", prefix(mcp, binding), separator(binding));
+            let rest = name.strip_prefix(head.as_str())?;
+            Some((head.len(), entry, rest))
+        })
+        .max_by_key(|(len, _, _)| *len)
+        .map(|(_, entry, rest)| (entry, rest))
+}
+
+/// A downstream tool to be given an exposed name
+pub struct ToolNameRequest {
+    pub binding_id: String,
+    pub prefix: String,
+    pub separator: String,
+    /// The server's name, which the exposed name must map back to
+    pub tool_name: String,
+    /// The name after the binding's rename, if any
+    pub exposed_tool_name: String,
+}
+
+pub struct AssignedName {
+    pub name: String,
+    /// The name this tool would have had, when another tool wanted it too
+    pub collision: Option<String>,
+}
+
+/// Names every tool `prefix<separator>tool`, limited to valid characters and
+/// 64 bytes. Names that are too long, or that more than one tool would get,
+/// are shortened and suffixed with a hash of the binding and tool, so each
+/// tool gets the same name every time and the router can map it back.
+pub fn assign_tool_names(requests: &[ToolNameRequest]) -> Vec<AssignedName> {
+    let wanted: Vec<String> = requests
+        .iter()
+        .map(|r| format!("{}{}{}", r.prefix, r.separator, sanitize(&r.exposed_tool_name)))
+        .collect();
+
+    let mut claims: HashMap<&str, usize> = HashMap::new();
+    for name in &wanted {
+        *claims.entry(name.as_str()).or_default() += 1;
+    }
+    let keeps_name = |name: &str| claims[name] == 1 && name.len() <= MAX_NAME_LEN;
+
+    // Names kept as is are taken first, so a suffixed name can't displace one
+    let mut taken: HashSet<String> = wanted.iter().filter(|n| keeps_name(n)).cloned().collect();
+
+    requests
+        .iter()
+        .zip(&wanted)
+        .map(|(request, name)| {
+            if keeps_name(name) {
+                return AssignedName {
+                    name: name.clone(),
+                    collision: None,
+                };
+            }
+
+            // Rehash in the unlikely case the suffixed name is taken too
+            let keep = name.len().min(MAX_NAME_LEN - HASH_LEN - 1);
+            let mut seed = format!("{}\0{}", request.binding_id, request.tool_name);
+            let mut suffixed = format!("{}_{}", &name[..keep], short_hash(&seed));
+            while taken.contains(&suffixed) {
+                seed.push('\0');
+                suffixed = format!("{}_{}", &name[..keep], short_hash(&seed));
+            }
+            taken.insert(suffixed.clone());
+
+            AssignedName {
+                name: suffixed,
+                collision: (claims[name.as_str()] > 1).then(|| name.clone()),
+            }
+        })
+        .collect()
+}
+
+/// Wraps a downstream resource URI (or URI template) so it routes back to its server
+pub fn namespaced_uri(mcp_prefix: &str, uri: &str) -> String {
+    format!("{}{}/{}", URI_SCHEME, mcp_prefix, uri)
+}
+
+/// Splits a namespaced URI into `(mcp_prefix, original uri)`
+pub fn split_namespaced_uri(uri: &str) -> Option<(&str, &str)> {
+    uri.strip_prefix(URI_SCHEME)?.split_once('/')
+}
+
+/// Replaces characters outside [a-zA-Z0-9_-] with `_`; the result is ASCII,
+/// so it can be cut at any byte
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect()
+}
+
+fn short_hash(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>()[..HASH_LEN]
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(binding_id: &str, prefix: &str, tool_name: &str) -> ToolNameRequest {
+        ToolNameRequest {
+            binding_id: binding_id.to_string(),
+            prefix: prefix.to_string(),
+            separator: DEFAULT_SEPARATOR.to_string(),
+            tool_name: tool_name.to_string(),
+            exposed_tool_name: tool_name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_plain_names_are_kept() {
+        let names = assign_tool_names(&[request("b1", "github", "search"), request("b2", "jira", "search")]);
+        assert_eq!(names[0].name, "github__search");
+        assert_eq!(names[1].name, "jira__search");
+        assert!(names.iter().all(|n| n.collision.is_none()));
+    }
+
+    #[test]
+    fn test_invalid_characters_are_replaced() {
+        let names = assign_tool_names(&[request("b1", "fs", "files.read")]);
+        assert_eq!(names[0].name, "fs__files_read");
+    }
+
+    #[test]
+    fn test_collisions_are_disambiguated() {
+        // Two servers named "my-db" and "my db" share a prefix
+        let names = assign_tool_names(&[request("b1", "my_db", "query"), request("b2", "my_db", "query")]);
+        assert_ne!(names[0].name, names[1].name);
+        for name in &names {
+            assert!(name.name.starts_with("my_db__query_"));
+            assert_eq!(name.collision.as_deref(), Some("my_db__query"));
+        }
+
+        // Deterministic across runs
+        let again = assign_tool_names(&[request("b1", "my_db", "query"), request("b2", "my_db", "query")]);
+        assert_eq!(again[0].name, names[0].name);
+    }
+
+    #[test]
+    fn test_suffixed_names_avoid_literal_names() {
+        let colliding = [request("b1", "my_db", "query"), request("b2", "my_db", "query")];
+        let suffixed = assign_tool_names(&colliding)[0].name.clone();
+
+        // A third tool that is literally named like the first suffixed name
+        let literal_tool = suffixed.strip_prefix("my_db__").unwrap();
+        let names = assign_tool_names(&[
+            request("b1", "my_db", "query"),
+            request("b2", "my_db", "query"),
+            request("b3", "my_db", literal_tool),
+        ]);
+
+        assert_eq!(names[2].name, suffixed);
+        assert_ne!(names[0].name, suffixed);
+        assert_ne!(names[0].name, names[1].name);
+        assert!(names[0].name.starts_with("my_db__query_"));
+    }
+
+    #[test]
+    fn test_separator_is_sanitized() {
+        let binding: ProjectMcpBinding = serde_json::from_value(serde_json::json!({
+            "id": "b1",
+            "project_id": "p1",
+            "mcp_id": "m1",
+            "enabled": true,
+            "overrides": [],
+            "tool_settings": { "separator": "\u{2192}" },
+        }))
+        .unwrap();
+        assert_eq!(separator(&binding), "_");
+
+        // Shortening would otherwise cut the multi-byte separator mid-character
+        let prefix = "p".repeat(56);
+        let mut long = request("b1", &prefix, "list_everything");
+        long.separator = separator(&binding);
+        let names = assign_tool_names(&[long]);
+        assert_eq!(names[0].name.len(), MAX_NAME_LEN);
+        assert!(names[0].name.starts_with(&format!("{}_", prefix)));
+    }
+
+    #[test]
+    fn test_long_names_are_shortened() {
+        let long_tool = "a".repeat(80);
+        let names = assign_tool_names(&[request("b1", "server", &long_tool)]);
+        assert_eq!(names[0].name.len(), MAX_NAME_LEN);
+        assert!(names[0].collision.is_none());
+
+        let other_tool = format!("{}b", "a".repeat(80));
+        let both = assign_tool_names(&[request("b1", "server", &long_tool), request("b1", "server", &other_tool)]);
+        assert_ne!(both[0].name, both[1].name);
+    }
+}
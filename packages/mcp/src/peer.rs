@@ -0,0 +1,227 @@
+use crate::mcp_client::{NotificationHandler, UpstreamHandler, TOOL_CALL_TIMEOUT};
+use crate::mcp_protocol::*;
+use crate::protocol_version;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// How long to wait for the AI client to answer a request we sent it
+const CLIENT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The AI client connected to the router
+pub struct ClientPeer {
+    /// Drained by a single writer (stdout, or an HTTP session's event
+    /// streams), so concurrent handlers never interleave their messages
+    output: mpsc::UnboundedSender<String>,
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<String, oneshot::Sender<Result<Value, JsonRpcError>>>>,
+    /// Capabilities the client declared during initialize
+    capabilities: Mutex<Value>,
+    /// Negotiated during initialize
+    protocol_version: Mutex<String>,
+    /// Requests from the client still being handled, by serialized id
+    in_progress: Mutex<HashMap<String, oneshot::Sender<()>>>,
+}
+
+/// A client request being handled; `cancelled` resolves if the client cancels it
+pub struct InProgress {
+    peer: Arc<ClientPeer>,
+    key: String,
+    pub cancelled: oneshot::Receiver<()>,
+}
+
+impl Drop for InProgress {
+    fn drop(&mut self) {
+        self.peer.in_progress.lock().unwrap().remove(&self.key);
+    }
+}
+
+impl ClientPeer {
+    pub fn new(output: mpsc::UnboundedSender<String>) -> Self {
+        ClientPeer {
+            output,
+            next_id: AtomicU64::new(1),
+            pending: Mutex::new(HashMap::new()),
+            capabilities: Mutex::new(json!({})),
+            protocol_version: Mutex::new(protocol_version::LATEST_VERSION.to_string()),
+            in_progress: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Queues one JSON-RPC message for the client
+    pub fn send(&self, message: &str) {
+        eprintln!("Sending message: {}", message);
+        if self.output.send(message.to_string()).is_err() {
+            eprintln!("Error writing message: client disconnected");
+        }
+    }
+
+    /// Sends a notification to the client
+    pub fn notify(&self, method: &str, params: Option<Value>) {
+        let notification = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: None,
+            method: method.to_string(),
+            params,
+        };
+        match serde_json::to_string(&notification) {
+            Ok(message) => self.send(&message),
+            Err(e) => eprintln!("Failed to serialize notification: {}", e),
+        }
+    }
+
+    /// Sends a request to the client and waits for its response
+    pub async fn request(&self, method: &str, params: Option<Value>) -> Result<Value, String> {
+        self.exchange(method, params, CLIENT_REQUEST_TIMEOUT)
+            .await
+            .map_err(|e| format!("Client error: {}", e.message))
+    }
+
+    async fn exchange(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        timeout: Duration,
+    ) -> Result<Value, JsonRpcError> {
+        // Prefixed ids can't collide with the ids the client uses for its own requests
+        let id = format!("mcp-toolkit-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id.clone(), tx);
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(id)),
+            method: method.to_string(),
+            params,
+        };
+        match serde_json::to_string(&request) {
+            Ok(message) => self.send(&message),
+            Err(e) => {
+                self.pending.lock().unwrap().remove(&id);
+                return Err(internal_error(format!("Failed to serialize request: {}", e)));
+            }
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(internal_error(format!("Request '{}' was dropped", method))),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                self.notify(
+                    "notifications/cancelled",
+                    Some(json!({ "requestId": id, "reason": "Request timed out" })),
+                );
+                Err(internal_error(format!("Client did not answer '{}' in time", method)))
+            }
+        }
+    }
+
+    pub fn set_protocol_version(&self, version: &str) {
+        *self.protocol_version.lock().unwrap() = version.to_string();
+    }
+
+    pub fn protocol_version(&self) -> String {
+        self.protocol_version.lock().unwrap().clone()
+    }
+
+    /// Records the capabilities from the client's initialize request
+    pub fn set_capabilities(&self, capabilities: Value) {
+        *self.capabilities.lock().unwrap() = capabilities;
+    }
+
+    /// Whether the client declared a capability (e.g. "roots") during initialize
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.lock().unwrap().get(capability).is_some()
+    }
+
+    /// Relays notifications from downstream servers to this client, for as
+    /// long as it is connected
+    pub fn notification_handler(self: &Arc<Self>) -> NotificationHandler {
+        let peer: Weak<ClientPeer> = Arc::downgrade(self);
+        Arc::new(move |method: String, params: Option<Value>| {
+            if let Some(peer) = peer.upgrade() {
+                peer.notify(&method, params);
+            }
+        })
+    }
+
+    /// Relays requests from downstream servers to this client under our own
+    /// ids; the answers go back to the server unchanged
+    pub fn upstream_handler(self: &Arc<Self>) -> UpstreamHandler {
+        let peer: Weak<ClientPeer> = Arc::downgrade(self);
+        Arc::new(move |method: String, params: Option<Value>| {
+            let peer = peer.upgrade();
+            Box::pin(async move {
+                let peer = peer.ok_or_else(|| internal_error("Client disconnected".to_string()))?;
+                // Only ask for what the client said it can do
+                let capability = method.split('/').next().unwrap_or_default();
+                if !peer.supports(capability) {
+                    return Err(JsonRpcError {
+                        code: -32601,
+                        message: format!("The connected client does not support {}", method),
+                        data: None,
+                    });
+                }
+                // Sampling and elicitation wait on the user
+                peer.exchange(&method, params, TOOL_CALL_TIMEOUT).await
+            })
+        })
+    }
+
+    /// Registers a request from the client so that it can be cancelled
+    pub fn track(self: &Arc<Self>, id: &Value) -> InProgress {
+        let (tx, rx) = oneshot::channel();
+        let key = id.to_string();
+        self.in_progress.lock().unwrap().insert(key.clone(), tx);
+        InProgress {
+            peer: self.clone(),
+            key,
+            cancelled: rx,
+        }
+    }
+
+    /// Handles `notifications/cancelled`. Returns false if the request
+    /// already finished or was never received.
+    pub fn cancel(&self, id: &Value) -> bool {
+        match self.in_progress.lock().unwrap().remove(&id.to_string()) {
+            Some(tx) => tx.send(()).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Routes a response from the client to the request waiting for it.
+    /// Returns false if the message is not a response to one of our requests.
+    pub fn handle_response(&self, message: &Value) -> bool {
+        if message.get("method").is_some() {
+            return false;
+        }
+        let id = match message.get("id").and_then(|id| id.as_str()) {
+            Some(id) => id,
+            None => return false,
+        };
+        let tx = match self.pending.lock().unwrap().remove(id) {
+            Some(tx) => tx,
+            None => return false,
+        };
+
+        let result = match message.get("error") {
+            Some(error) => Err(serde_json::from_value(error.clone()).unwrap_or_else(|_| {
+                internal_error(format!("Invalid error from client: {}", error))
+            })),
+            None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+        };
+        let _ = tx.send(result);
+        true
+    }
+}
+
+fn internal_error(message: String) -> JsonRpcError {
+    JsonRpcError {
+        code: -32603,
+        message,
+        data: None,
+    }
+}
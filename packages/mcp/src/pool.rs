@@ -0,0 +1,158 @@
+use crate::mcp_client::McpClient;
+use crate::models::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// Identifies a pooled client: one process per session, server, binding and
+/// resolved environment. Sessions don't share processes, so whatever a server
+/// asks of its client (sampling, elicitation, roots) reaches the session using it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolKey {
+    pub session_id: String,
+    pub mcp_id: String,
+    pub binding_id: String,
+    env_hash: u64,
+}
+
+impl PoolKey {
+    pub fn new(session_id: &str, mcp: &Mcp, binding: &ProjectMcpBinding, env_vars: &[EnvVar]) -> Self {
+        // Hash the sorted env so that the order of overrides doesn't matter
+        let mut pairs: Vec<(&str, &str)> = env_vars
+            .iter()
+            .map(|v| (v.key.as_str(), v.value.as_str()))
+            .collect();
+        pairs.sort();
+
+        let mut hasher = DefaultHasher::new();
+        pairs.hash(&mut hasher);
+
+        PoolKey {
+            session_id: session_id.to_string(),
+            mcp_id: mcp.id.clone(),
+            binding_id: binding.id.clone(),
+            env_hash: hasher.finish(),
+        }
+    }
+}
+
+/// A pooled client, locked while it is being spawned so concurrent callers share one process
+type Slot = Arc<tokio::sync::Mutex<Option<Arc<McpClient>>>>;
+
+/// Keeps initialized downstream MCP clients alive across requests
+pub struct ClientPool {
+    clients: Mutex<HashMap<PoolKey, Slot>>,
+    /// Keys each client session uses, by session id: those it listed tools
+    /// from last, plus any it has called since. The rest are shut down.
+    in_use: Mutex<HashMap<String, HashSet<PoolKey>>>,
+}
+
+impl ClientPool {
+    pub fn new() -> Self {
+        ClientPool {
+            clients: Mutex::new(HashMap::new()),
+            in_use: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns a live, initialized client for the binding, spawning one if
+    /// needed, and records it as in use by the session
+    pub async fn get_or_spawn(
+        &self,
+        session_id: &str,
+        mcp: &Mcp,
+        binding: &ProjectMcpBinding,
+        env_vars: &[EnvVar],
+    ) -> Result<Arc<McpClient>, String> {
+        let key = PoolKey::new(session_id, mcp, binding, env_vars);
+        // Recorded before the slot is created, so a concurrent release can't
+        // drop the new client
+        self.in_use
+            .lock()
+            .unwrap()
+            .entry(session_id.to_string())
+            .or_default()
+            .insert(key.clone());
+        let slot = self.clients.lock().unwrap().entry(key).or_default().clone();
+
+        // Only this key is locked, so a slow server doesn't block the others
+        let mut slot = slot.lock().await;
+        if let Some(client) = slot.as_ref() {
+            if client.is_alive() {
+                return Ok(client.clone());
+            }
+            eprintln!("[ClientPool] Client for {} has exited, respawning", mcp.name);
+            *slot = None;
+        }
+
+        let client = Arc::new(McpClient::connect(mcp, env_vars).await?);
+
+        eprintln!("[ClientPool] Pooled client for: {}", mcp.name);
+        *slot = Some(client.clone());
+        Ok(client)
+    }
+
+    /// Whether the session's process for the binding is "running", still
+    /// "starting", or "stopped"
+    pub fn status(
+        &self,
+        session_id: &str,
+        mcp: &Mcp,
+        binding: &ProjectMcpBinding,
+        env_vars: &[EnvVar],
+    ) -> &'static str {
+        let key = PoolKey::new(session_id, mcp, binding, env_vars);
+        let Some(slot) = self.clients.lock().unwrap().get(&key).cloned() else {
+            return "stopped";
+        };
+
+        // The slot stays locked while the client is spawned and initialized
+        let status = match slot.try_lock() {
+            Ok(client) if client.as_ref().is_some_and(|c| c.is_alive()) => "running",
+            Ok(_) => "stopped",
+            Err(_) => "starting",
+        };
+        status
+    }
+
+    /// Replaces the clients a session uses with `active`, the bindings it
+    /// lists tools from, and shuts down those no session uses
+    pub fn retain(&self, session_id: &str, active: HashSet<PoolKey>) {
+        self.in_use.lock().unwrap().insert(session_id.to_string(), active);
+        self.release_unused();
+    }
+
+    /// Forgets a closed session, shutting down its clients
+    pub fn release(&self, session_id: &str) {
+        self.in_use.lock().unwrap().remove(session_id);
+        self.release_unused();
+    }
+
+    fn release_unused(&self) {
+        let in_use = self.in_use.lock().unwrap();
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain(|key, _| {
+            let keep = in_use.values().any(|keys| keys.contains(key));
+            if !keep {
+                eprintln!(
+                    "[ClientPool] Releasing unused client for {} (session {})",
+                    key.mcp_id, key.session_id
+                );
+            }
+            keep
+        });
+    }
+
+    /// Shuts down every pooled client
+    pub async fn shutdown_all(&self) {
+        let slots: Vec<Slot> = self.clients.lock().unwrap().drain().map(|(_, slot)| slot).collect();
+        eprintln!("[ClientPool] Shutting down {} client(s)", slots.len());
+
+        for slot in slots {
+            if let Some(client) = slot.lock().await.take() {
+                client.close().await;
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use crate::models::*;
+use crate::storage::Storage;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Resolves the project to serve: the explicit selector when given, otherwise
+/// the registered project containing the current working directory
+pub fn resolve_project(storage: &Storage, selector: Option<&str>) -> Result<Option<Project>, String> {
+    if let Some(selector) = selector {
+        return storage.find_project(selector).map(Some);
+    }
+
+    let cwd = match std::env::current_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("[projects] Could not read working directory: {}", e);
+            return Ok(None);
+        }
+    };
+
+    let projects = storage.get_projects()?;
+    let project = find_project_for_path(&projects, &cwd);
+    if let Some(p) = &project {
+        eprintln!(
+            "[projects] Detected project '{}' from working directory {}",
+            p.name,
+            cwd.display()
+        );
+    }
+
+    Ok(project)
+}
+
+/// Finds the project whose path is the longest prefix of `dir`, with symlinks resolved.
+/// Projects without an absolute path are skipped, since an empty or relative
+/// path would be a prefix of every directory.
+pub fn find_project_for_path(projects: &[Project], dir: &Path) -> Option<Project> {
+    let dir = canonical(dir);
+
+    projects
+        .iter()
+        .filter(|project| !project.path.trim().is_empty() && Path::new(&project.path).is_absolute())
+        .filter_map(|project| {
+            let project_path = canonical(Path::new(&project.path));
+            // Path::starts_with compares whole components, so /a/foo doesn't match /a/foobar
+            dir.starts_with(&project_path)
+                .then(|| (project_path.components().count(), project))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, project)| project.clone())
+}
+
+/// Converts a `file://` root URI sent by the client into a local path
+pub fn root_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    // Drop an optional authority, e.g. file://localhost/home/user
+    let path = &path[path.find('/')?..];
+    let decoded = percent_decode(path)?;
+
+    // file:///C:/Users/... on Windows
+    let bytes = decoded.as_bytes();
+    if bytes.len() > 2 && bytes[0] == b'/' && bytes[2] == b':' && bytes[1].is_ascii_alphabetic() {
+        return Some(PathBuf::from(&decoded[1..]));
+    }
+
+    Some(PathBuf::from(decoded))
+}
+
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(name: &str, path: &Path) -> Project {
+        Project {
+            id: format!("{}-id", name),
+            name: name.to_string(),
+            path: path.to_string_lossy().to_string(),
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    fn temp_root() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("mcp-toolkit-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let root = temp_root();
+        let nested = root.join("apps").join("web");
+        fs::create_dir_all(nested.join("src")).unwrap();
+
+        let projects = vec![project("mono", &root), project("web", &nested)];
+
+        let found = find_project_for_path(&projects, &nested.join("src")).unwrap();
+        assert_eq!(found.name, "web");
+
+        let found = find_project_for_path(&projects, &root.join("apps")).unwrap();
+        assert_eq!(found.name, "mono");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_sibling_with_common_prefix_does_not_match() {
+        let root = temp_root();
+        fs::create_dir_all(root.join("api")).unwrap();
+        fs::create_dir_all(root.join("api-v2")).unwrap();
+
+        let projects = vec![project("api", &root.join("api"))];
+        assert!(find_project_for_path(&projects, &root.join("api-v2")).is_none());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_empty_or_relative_path_does_not_match() {
+        let root = temp_root();
+
+        let projects = vec![project("empty", Path::new("")), project("relative", Path::new("."))];
+        assert!(find_project_for_path(&projects, &root).is_none());
+        // "." would otherwise resolve against the router's own working directory
+        assert!(find_project_for_path(&projects, &std::env::current_dir().unwrap()).is_none());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_root_uri_to_path() {
+        assert_eq!(
+            root_uri_to_path("file:///home/dev/my%20project"),
+            Some(PathBuf::from("/home/dev/my project"))
+        );
+        assert_eq!(
+            root_uri_to_path("file://localhost/srv/api"),
+            Some(PathBuf::from("/srv/api"))
+        );
+        assert_eq!(
+            root_uri_to_path("file:///C:/Users/dev/api"),
+            Some(PathBuf::from("C:/Users/dev/api"))
+        );
+        assert_eq!(root_uri_to_path("https://example.com/repo"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_working_directory() {
+        let root = temp_root();
+        let real = root.join("real");
+        fs::create_dir_all(&real).unwrap();
+        let link = root.join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let projects = vec![project("real", &real)];
+        let found = find_project_for_path(&projects, &link).unwrap();
+        assert_eq!(found.name, "real");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+use crate::aggregate;
+use crate::mcp_protocol::*;
+use crate::naming;
+use crate::router::Router;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub async fn handle_prompts_list(id: Option<Value>, router: &Arc<Router>) -> JsonRpcResponse {
+    let (results, failures) =
+        match aggregate::fan_out_list(router, "prompts", "prompts/list", "prompts").await {
+            Ok(r) => r,
+            Err(e) => return JsonRpcResponse::error(id, -32000, format!("Failed to get MCPs: {}", e)),
+        };
+
+    let mut prompts = Vec::new();
+    for (mcp, binding, items) in results {
+        for mut prompt in items {
+            // Same prefix__name namespacing as tools
+            let name = match prompt.get("name").and_then(|n| n.as_str()) {
+                Some(name) => naming::prefixed_name(&mcp, &binding, name),
+                None => {
+                    eprintln!("[handle_prompts_list] Prompt missing 'name' field, skipping");
+                    continue;
+                }
+            };
+            prompt["name"] = json!(name);
+            prompts.push(prompt);
+        }
+    }
+
+    eprintln!("[handle_prompts_list] Total prompts listed: {}", prompts.len());
+    let result = ListPromptsResult {
+        prompts,
+        meta: aggregate::failures_meta(failures),
+    };
+    JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
+}
+
+pub async fn handle_prompts_get(
+    id: Option<Value>,
+    request: &JsonRpcRequest,
+    router: &Router,
+) -> JsonRpcResponse {
+    let mut params = match request.params.clone() {
+        Some(params) => params,
+        None => return JsonRpcResponse::error(id, -32602, "Missing params".to_string()),
+    };
+
+    let prompt_name = match params.get("name").and_then(|n| n.as_str()) {
+        Some(name) => name.to_string(),
+        None => return JsonRpcResponse::error(id, -32602, "Missing 'name' param".to_string()),
+    };
+
+    let bindings = match router.bindings() {
+        Ok(bindings) => bindings,
+        Err(e) => return JsonRpcResponse::error(id, -32000, format!("Failed to get MCPs: {}", e)),
+    };
+    let ((mcp, binding), actual_prompt_name) = match naming::split_prefixed(&bindings, &prompt_name) {
+        Some(parts) => parts,
+        None => {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                format!("No bound MCP matches the prefix of prompt: {}", prompt_name),
+            )
+        }
+    };
+
+    let env_vars = router.resolve_env_vars(mcp, binding);
+    let client = match router.client(mcp, binding, &env_vars).await {
+        Ok(c) => c,
+        Err(e) => return JsonRpcResponse::error(id, -32000, e),
+    };
+
+    // Forward with the name the downstream server knows
+    params["name"] = json!(actual_prompt_name);
+
+    match client.request("prompts/get", params).await {
+        Ok(result) => JsonRpcResponse::success(id, result),
+        Err(e) => JsonRpcResponse::error(id, -32000, e),
+    }
+}
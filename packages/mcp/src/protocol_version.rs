@@ -0,0 +1,134 @@
+use crate::mcp_protocol::{CallToolResult, McpTool, ToolContent};
+
+/// Protocol versions the router speaks, on both sides, newest first
+pub const SUPPORTED_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+pub const LATEST_VERSION: &str = SUPPORTED_VERSIONS[0];
+
+/// Tool annotations and audio content
+const ANNOTATIONS_VERSION: &str = "2025-03-26";
+
+/// Tool titles, outputSchema, structuredContent and resource links
+const STRUCTURED_OUTPUT_VERSION: &str = "2025-06-18";
+
+pub fn is_supported(version: &str) -> bool {
+    SUPPORTED_VERSIONS.contains(&version)
+}
+
+/// The version to answer an initialize request with: the client's when we
+/// support it, otherwise our latest, which the client may then reject
+pub fn negotiate(requested: &str) -> &'static str {
+    SUPPORTED_VERSIONS
+        .iter()
+        .find(|v| **v == requested)
+        .unwrap_or(&LATEST_VERSION)
+}
+
+/// Versions are dates, so they order as strings
+fn at_least(version: &str, minimum: &str) -> bool {
+    version >= minimum
+}
+
+/// Drops tool fields the client's protocol version doesn't define
+pub fn adapt_tools(version: &str, tools: &mut [McpTool]) {
+    for tool in tools {
+        if !at_least(version, STRUCTURED_OUTPUT_VERSION) {
+            tool.title = None;
+            tool.output_schema = None;
+        }
+        if !at_least(version, ANNOTATIONS_VERSION) {
+            tool.annotations = None;
+        }
+    }
+}
+
+/// Rewrites a tool result into shapes the client's protocol version defines:
+/// structured output falls back to its text form, and newer content types
+/// become text blocks describing them
+pub fn adapt_tool_result(version: &str, result: &mut CallToolResult) {
+    if !at_least(version, STRUCTURED_OUTPUT_VERSION) {
+        if let Some(structured) = result.structured_content.take() {
+            // Servers should already send the text form; add it if they didn't
+            if result.content.is_empty() {
+                result.content.push(text(structured.to_string()));
+            }
+        }
+    }
+
+    for block in result.content.iter_mut() {
+        let replacement = match block {
+            ToolContent::ResourceLink { uri, name, .. } if !at_least(version, STRUCTURED_OUTPUT_VERSION) => {
+                text(format!("Resource {}: {}", name, uri))
+            }
+            ToolContent::Audio { mime_type, .. } if !at_least(version, ANNOTATIONS_VERSION) => {
+                text(format!("[{} audio omitted: not supported by this client]", mime_type))
+            }
+            _ => continue,
+        };
+        *block = replacement;
+    }
+}
+
+fn text(text: String) -> ToolContent {
+    ToolContent::Text {
+        text,
+        annotations: None,
+        meta: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate("2025-03-26"), "2025-03-26");
+        assert_eq!(negotiate("2024-11-05"), "2024-11-05");
+        assert_eq!(negotiate("2099-01-01"), LATEST_VERSION);
+        assert_eq!(negotiate("garbage"), LATEST_VERSION);
+    }
+
+    #[test]
+    fn test_adapt_tool_result() {
+        let result = || -> CallToolResult {
+            serde_json::from_value(json!({
+                "content": [
+                    {"type": "resource_link", "uri": "file:///a.txt", "name": "a"},
+                    {"type": "audio", "data": "AAA=", "mimeType": "audio/wav"}
+                ],
+                "structuredContent": {"ok": true}
+            }))
+            .unwrap()
+        };
+
+        let mut latest = result();
+        adapt_tool_result(LATEST_VERSION, &mut latest);
+        assert_eq!(serde_json::to_value(&latest).unwrap(), serde_json::to_value(result()).unwrap());
+
+        let mut older = result();
+        adapt_tool_result("2025-03-26", &mut older);
+        assert_eq!(
+            serde_json::to_value(&older).unwrap(),
+            json!({
+                "content": [
+                    {"type": "text", "text": "Resource a: file:///a.txt"},
+                    {"type": "audio", "data": "AAA=", "mimeType": "audio/wav"}
+                ]
+            })
+        );
+
+        let mut oldest = result();
+        adapt_tool_result("2024-11-05", &mut oldest);
+        assert!(matches!(&oldest.content[1], ToolContent::Text { text, .. } if text.contains("audio/wav")));
+
+        let mut structured_only: CallToolResult =
+            serde_json::from_value(json!({"content": [], "structuredContent": {"n": 1}})).unwrap();
+        adapt_tool_result("2025-03-26", &mut structured_only);
+        assert_eq!(
+            serde_json::to_value(&structured_only).unwrap(),
+            json!({"content": [{"type": "text", "text": "{\"n\":1}"}]})
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use crate::aggregate;
+use crate::mcp_protocol::*;
+use crate::naming;
+use crate::router::Router;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub async fn handle_resources_list(id: Option<Value>, router: &Arc<Router>) -> JsonRpcResponse {
+    let (results, failures) =
+        match aggregate::fan_out_list(router, "resources", "resources/list", "resources").await {
+            Ok(r) => r,
+            Err(e) => return JsonRpcResponse::error(id, -32000, format!("Failed to get MCPs: {}", e)),
+        };
+
+    let mut resources = Vec::new();
+    for (mcp, binding, items) in results {
+        for mut resource in items {
+            // Namespace the URI so resources/read routes back to this server
+            let uri = match resource.get("uri").and_then(|u| u.as_str()) {
+                Some(uri) => naming::namespaced_uri(&naming::prefix(&mcp, &binding), uri),
+                None => continue,
+            };
+            resource["uri"] = json!(uri);
+            resources.push(resource);
+        }
+    }
+
+    eprintln!("[handle_resources_list] Total resources listed: {}", resources.len());
+    let result = ListResourcesResult {
+        resources,
+        meta: aggregate::failures_meta(failures),
+    };
+    JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
+}
+
+pub async fn handle_resource_templates_list(
+    id: Option<Value>,
+    router: &Arc<Router>,
+) -> JsonRpcResponse {
+    let (results, failures) = match aggregate::fan_out_list(
+        router,
+        "resources",
+        "resources/templates/list",
+        "resourceTemplates",
+    )
+    .await
+    {
+        Ok(r) => r,
+        Err(e) => return JsonRpcResponse::error(id, -32000, format!("Failed to get MCPs: {}", e)),
+    };
+
+    let mut resource_templates = Vec::new();
+    for (mcp, binding, items) in results {
+        for mut template in items {
+            // The prefix sits outside the {variables}, so expanded URIs stay routable
+            let uri_template = match template.get("uriTemplate").and_then(|u| u.as_str()) {
+                Some(uri) => naming::namespaced_uri(&naming::prefix(&mcp, &binding), uri),
+                None => continue,
+            };
+            template["uriTemplate"] = json!(uri_template);
+            resource_templates.push(template);
+        }
+    }
+
+    let result = ListResourceTemplatesResult {
+        resource_templates,
+        meta: aggregate::failures_meta(failures),
+    };
+    JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
+}
+
+pub async fn handle_resources_read(
+    id: Option<Value>,
+    request: &JsonRpcRequest,
+    router: &Router,
+) -> JsonRpcResponse {
+    let (mcp_prefix, params) = match parse_resource_params(request) {
+        Ok(p) => p,
+        Err(e) => return JsonRpcResponse::error(id, -32602, e),
+    };
+
+    let client = match router.client_for_prefix(&mcp_prefix).await {
+        Ok(c) => c,
+        Err(e) => return JsonRpcResponse::error(id, -32000, e),
+    };
+
+    match client.request("resources/read", params).await {
+        Ok(mut result) => {
+            // Contents carry the downstream URI; namespace it like in resources/list
+            if let Some(contents) = result.get_mut("contents").and_then(|c| c.as_array_mut()) {
+                for content in contents {
+                    if let Some(uri) = content.get("uri").and_then(|u| u.as_str()) {
+                        let uri = naming::namespaced_uri(&mcp_prefix, uri);
+                        content["uri"] = json!(uri);
+                    }
+                }
+            }
+            JsonRpcResponse::success(id, result)
+        }
+        Err(e) => JsonRpcResponse::error(id, -32000, e),
+    }
+}
+
+/// Handles resources/subscribe and resources/unsubscribe
+pub async fn handle_resources_subscription(
+    id: Option<Value>,
+    request: &JsonRpcRequest,
+    router: &Router,
+) -> JsonRpcResponse {
+    let (mcp_prefix, params) = match parse_resource_params(request) {
+        Ok(p) => p,
+        Err(e) => return JsonRpcResponse::error(id, -32602, e),
+    };
+
+    let client = match router.client_for_prefix(&mcp_prefix).await {
+        Ok(c) => c,
+        Err(e) => return JsonRpcResponse::error(id, -32000, e),
+    };
+
+    let supports_subscribe = client
+        .capability("resources")
+        .and_then(|r| r.get("subscribe").and_then(|s| s.as_bool()))
+        .unwrap_or(false);
+    if !supports_subscribe {
+        return JsonRpcResponse::error(
+            id,
+            -32601,
+            format!("MCP '{}' does not support resource subscriptions", client.mcp_name),
+        );
+    }
+
+    match client.request(&request.method, params).await {
+        Ok(result) => JsonRpcResponse::success(id, result),
+        Err(e) => JsonRpcResponse::error(id, -32000, e),
+    }
+}
+
+/// Extracts the server prefix from the namespaced `uri` param and returns the
+/// params to forward, with the original downstream URI
+fn parse_resource_params(request: &JsonRpcRequest) -> Result<(String, Value), String> {
+    let mut params = request.params.clone().ok_or("Missing params")?;
+
+    let uri = params
+        .get("uri")
+        .and_then(|u| u.as_str())
+        .ok_or("Missing 'uri' param")?
+        .to_string();
+
+    let (mcp_prefix, original_uri) = naming::split_namespaced_uri(&uri)
+        .ok_or_else(|| format!("Unknown resource URI: {}", uri))?;
+
+    params["uri"] = json!(original_uri);
+    Ok((mcp_prefix.to_string(), params))
+}
@@ -0,0 +1,222 @@
+use crate::mcp_client::{McpClient, NotificationHandler, UpstreamHandler};
+use crate::models::*;
+use crate::naming;
+use crate::pool::ClientPool;
+use crate::projects;
+use crate::secrets::SecretManager;
+use crate::storage::Storage;
+use crate::tool_cache::{CacheKey, ToolCache};
+use crate::tool_overrides;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Routes one client session's requests to downstream MCP servers.
+/// Storage, the client pool and the tool cache are shared between sessions,
+/// but each session gets its own clients from the pool.
+pub struct Router {
+    pub storage: Arc<Storage>,
+    pub secret_manager: Arc<SecretManager>,
+    pub pool: Arc<ClientPool>,
+    pub tools: Arc<ToolCache>,
+    /// Identifies this session's clients in the pool
+    pub session_id: String,
+    /// Project chosen at startup, from the selector or the working directory
+    default_project: Option<Project>,
+    /// Set when the project was selected explicitly; client roots can't replace it
+    pinned: bool,
+    project: Mutex<Option<Project>>,
+    /// Relays server-initiated requests to this session's client
+    upstream: Mutex<Option<UpstreamHandler>>,
+    /// Relays server notifications to this session's client
+    notifier: Mutex<Option<NotificationHandler>>,
+}
+
+impl Router {
+    pub fn new(
+        storage: Storage,
+        secret_manager: SecretManager,
+        project: Option<Project>,
+        pinned: bool,
+    ) -> Self {
+        Router {
+            storage: Arc::new(storage),
+            secret_manager: Arc::new(secret_manager),
+            pool: Arc::new(ClientPool::new()),
+            tools: Arc::new(ToolCache::new()),
+            session_id: "stdio".to_string(),
+            default_project: project.clone(),
+            pinned,
+            project: Mutex::new(project),
+            upstream: Mutex::new(None),
+            notifier: Mutex::new(None),
+        }
+    }
+
+    /// A router for another client session, starting from the same project
+    /// selection but with its own roots and client
+    pub fn new_session(&self, session_id: &str) -> Router {
+        Router {
+            storage: self.storage.clone(),
+            secret_manager: self.secret_manager.clone(),
+            pool: self.pool.clone(),
+            tools: self.tools.clone(),
+            session_id: session_id.to_string(),
+            default_project: self.default_project.clone(),
+            pinned: self.pinned,
+            project: Mutex::new(self.default_project.clone()),
+            upstream: Mutex::new(None),
+            notifier: Mutex::new(None),
+        }
+    }
+
+    /// The project whose bindings are currently exposed
+    pub fn project(&self) -> Option<Project> {
+        self.project.lock().unwrap().clone()
+    }
+
+    pub fn set_upstream(&self, handler: UpstreamHandler) {
+        *self.upstream.lock().unwrap() = Some(handler);
+    }
+
+    pub fn set_notifier(&self, handler: NotificationHandler) {
+        *self.notifier.lock().unwrap() = Some(handler);
+    }
+
+    /// This session's pooled, initialized client for the binding. Its sampling,
+    /// elicitation and roots requests and its resource notifications go to
+    /// this session's client.
+    pub async fn client(
+        &self,
+        mcp: &Mcp,
+        binding: &ProjectMcpBinding,
+        env_vars: &[EnvVar],
+    ) -> Result<Arc<McpClient>, String> {
+        let client = self.pool.get_or_spawn(&self.session_id, mcp, binding, env_vars).await?;
+        if let Some(handler) = self.upstream.lock().unwrap().clone() {
+            client.set_upstream(handler);
+        }
+        if let Some(notify) = self.notifier.lock().unwrap().clone() {
+            // Resource URIs are namespaced the way resources/list exposed them
+            let prefix = naming::prefix(mcp, binding);
+            client.set_notifier(Arc::new(move |method: String, mut params: Option<Value>| {
+                if let Some(uri) = params.as_mut().and_then(|p| p.get_mut("uri")) {
+                    if let Some(original) = uri.as_str() {
+                        *uri = json!(naming::namespaced_uri(&prefix, original));
+                    }
+                }
+                notify(method, params)
+            }));
+        }
+        Ok(client)
+    }
+
+    /// Re-scopes the router to the project containing one of the client roots,
+    /// falling back to the startup project when none matches.
+    /// Returns true if the active project changed.
+    pub fn apply_roots(&self, roots: &[PathBuf]) -> Result<bool, String> {
+        if self.pinned {
+            eprintln!("[Router] Project was selected explicitly, ignoring client roots");
+            return Ok(false);
+        }
+
+        let registered = self.storage.get_projects()?;
+        let selected = roots
+            .iter()
+            .find_map(|root| projects::find_project_for_path(&registered, root))
+            .or_else(|| self.default_project.clone());
+
+        let mut project = self.project.lock().unwrap();
+        let changed = project.as_ref().map(|p| &p.id) != selected.as_ref().map(|p| &p.id);
+        if changed {
+            match &selected {
+                Some(p) => eprintln!("[Router] Client roots selected project: {} ({})", p.name, p.id),
+                None => eprintln!("[Router] No project matches the client roots"),
+            }
+            *project = selected;
+        }
+
+        Ok(changed)
+    }
+
+    /// Enabled bindings visible to the client: the active project's, or every
+    /// project's when none is selected
+    pub fn bindings(&self) -> Result<Vec<(Mcp, ProjectMcpBinding)>, String> {
+        let project_id = self.project().map(|p| p.id);
+        self.storage.get_enabled_mcps_with_bindings(project_id.as_deref())
+    }
+
+    /// Key of the persisted tool names: the active project, or every project
+    pub fn scope(&self) -> String {
+        self.project().map(|p| p.id).unwrap_or_else(|| "*".to_string())
+    }
+
+    /// Finds the bound MCP whose exposed prefix matches
+    pub fn find_by_prefix(&self, prefix: &str) -> Result<Option<(Mcp, ProjectMcpBinding)>, String> {
+        Ok(self
+            .bindings()?
+            .into_iter()
+            .find(|(mcp, binding)| naming::prefix(mcp, binding) == prefix))
+    }
+
+    /// Maps an exposed tool name back to its binding and the server's tool
+    /// name, through the names saved by the last tools/list or, before one,
+    /// by the binding's prefix and rename
+    pub fn resolve_tool(&self, exposed_name: &str) -> Result<Option<(Mcp, ProjectMcpBinding, String)>, String> {
+        let bindings = self.bindings()?;
+
+        if let Some(saved) = self.storage.find_tool_name(&self.scope(), exposed_name)? {
+            if let Some((mcp, binding)) = bindings.iter().find(|(_, b)| b.id == saved.binding_id) {
+                return Ok(Some((mcp.clone(), binding.clone(), saved.tool_name)));
+            }
+        }
+
+        Ok(naming::split_prefixed(&bindings, exposed_name).and_then(|((mcp, binding), rest)| {
+            let tool_name = tool_overrides::original_name(&binding.tool_settings, rest)?;
+            Some((mcp.clone(), binding.clone(), tool_name.to_string()))
+        }))
+    }
+
+    /// A pooled, initialized client for the bound MCP with the given prefix
+    pub async fn client_for_prefix(&self, prefix: &str) -> Result<Arc<McpClient>, String> {
+        let (mcp, binding) = self
+            .find_by_prefix(prefix)?
+            .ok_or_else(|| format!("MCP not found for prefix: {}", prefix))?;
+        let env_vars = self.resolve_env_vars(&mcp, &binding);
+        self.client(&mcp, &binding, &env_vars).await
+    }
+
+    /// The server's definition of a tool, if its tool list has been discovered
+    pub fn cached_tool(&self, mcp: &Mcp, binding: &ProjectMcpBinding, tool_name: &str) -> Option<Value> {
+        let (tools, _) = self.tools.get(&self.storage, &CacheKey::new(mcp, binding))?;
+        tools
+            .into_iter()
+            .find(|t| t.get("name").and_then(|n| n.as_str()) == Some(tool_name))
+    }
+
+    /// Merges the binding overrides into the MCP env vars and decrypts secrets
+    pub fn resolve_env_vars(&self, mcp: &Mcp, binding: &ProjectMcpBinding) -> Vec<EnvVar> {
+        // Merge env vars with overrides
+        let mut env_vars = mcp.config.env_vars.clone();
+        for override_var in &binding.overrides {
+            if let Some(existing) = env_vars.iter_mut().find(|v| v.key == override_var.key) {
+                existing.value = override_var.value.clone();
+            } else {
+                env_vars.push(override_var.clone());
+            }
+        }
+
+        // Decrypt secrets
+        for env_var in env_vars.iter_mut() {
+            if env_var.is_secret {
+                if let Ok(Some(encrypted)) = self.storage.get_encrypted_secret(&env_var.value) {
+                    if let Ok(decrypted) = self.secret_manager.decrypt(&encrypted) {
+                        env_var.value = decrypted;
+                    }
+                }
+            }
+        }
+
+        env_vars
+    }
+}
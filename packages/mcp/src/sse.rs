@@ -0,0 +1,114 @@
+/// A single Server-Sent Event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+    pub id: Option<String>,
+}
+
+/// Incremental parser for `text/event-stream` bodies. Bytes can be fed in
+/// arbitrary chunks; complete events are returned as soon as they end.
+#[derive(Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+
+        // Only decode complete lines so multi-byte characters split across chunks survive
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+
+            if let Some(event) = self.process_line(line) {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        // A blank line dispatches the event
+        if line.is_empty() {
+            if self.data.is_empty() {
+                self.event = None;
+                return None;
+            }
+            return Some(SseEvent {
+                event: self.event.take(),
+                data: std::mem::take(&mut self.data).join("\n"),
+                id: self.id.clone(),
+            });
+        }
+
+        // Comments, used as keep-alives
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" => self.id = Some(value.to_string()),
+            _ => {}
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_events_split_across_chunks() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"event: message\nda").is_empty());
+
+        let events = parser.feed(b"ta: {\"a\":1}\nid: 7\n\n");
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                event: Some("message".to_string()),
+                data: "{\"a\":1}".to_string(),
+                id: Some("7".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_multiline_data_comments_and_crlf() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b": keep-alive\r\n\r\ndata: one\r\ndata: two\r\n\r\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "one\ntwo");
+        assert_eq!(events[0].event, None);
+    }
+
+    #[test]
+    fn test_multibyte_character_split_across_chunks() {
+        let mut parser = SseParser::new();
+        let bytes = "data: caf\u{e9}\n\n".as_bytes();
+        let (first, second) = bytes.split_at(bytes.len() - 3);
+        assert!(parser.feed(first).is_empty());
+        assert_eq!(parser.feed(second)[0].data, "caf\u{e9}");
+    }
+}
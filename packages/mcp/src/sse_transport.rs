@@ -0,0 +1,194 @@
+use crate::http_transport::{headers_from_env, http_client};
+use crate::models::*;
+use crate::sse::{SseEvent, SseParser};
+use reqwest::header::{HeaderMap, ACCEPT, CONTENT_TYPE};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How long to wait for the stream to open and announce its `endpoint`
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Legacy HTTP+SSE transport (protocol 2024-11-05): a long-lived GET stream
+/// carries every server message, and client messages are POSTed to the URL
+/// announced in its `endpoint` event
+pub struct SseTransport {
+    client: reqwest::Client,
+    endpoint: String,
+    headers: HeaderMap,
+    reader: tokio::task::JoinHandle<()>,
+    closed: Arc<AtomicBool>,
+    mcp_name: String,
+}
+
+impl SseTransport {
+    /// Opens the event stream and waits for the server to announce its message
+    /// endpoint; every message on the stream is then sent to `incoming`
+    pub async fn connect(
+        mcp: &Mcp,
+        env_vars: &[EnvVar],
+        incoming: mpsc::UnboundedSender<Value>,
+    ) -> Result<Self, String> {
+        let url = mcp
+            .config
+            .http_url
+            .as_ref()
+            .ok_or("No HTTP URL specified")?;
+        let base = reqwest::Url::parse(url).map_err(|e| format!("Invalid HTTP URL '{}': {}", url, e))?;
+        let headers = headers_from_env(env_vars)?;
+        let client = http_client()?;
+
+        eprintln!("[SseTransport] Opening event stream for: {}", mcp.name);
+
+        let mut parser = SseParser::new();
+        let (response, endpoint, backlog) = tokio::time::timeout(ENDPOINT_TIMEOUT, async {
+            let mut response = client
+                .get(base.clone())
+                .headers(headers.clone())
+                .header(ACCEPT, "text/event-stream")
+                .send()
+                .await
+                .map_err(|e| format!("Failed to open event stream for {}: {}", mcp.name, e))?;
+
+            if !response.status().is_success() {
+                return Err(format!(
+                    "HTTP error {} opening event stream for {}",
+                    response.status(),
+                    mcp.name
+                ));
+            }
+
+            let (endpoint, backlog) = read_endpoint(&mut response, &mut parser).await?;
+            Ok((response, endpoint, backlog))
+        })
+        .await
+        .map_err(|_| format!("Timed out waiting for the endpoint event from {}", mcp.name))??;
+
+        // The endpoint is usually relative to the stream URL
+        let endpoint = base
+            .join(&endpoint)
+            .map_err(|e| format!("Invalid endpoint '{}' from {}: {}", endpoint, mcp.name, e))?
+            .to_string();
+        eprintln!("[SseTransport] Message endpoint for {}: {}", mcp.name, endpoint);
+
+        let closed = Arc::new(AtomicBool::new(false));
+        let reader = tokio::spawn(read_stream(
+            response,
+            parser,
+            backlog,
+            incoming,
+            closed.clone(),
+            mcp.name.clone(),
+        ));
+
+        Ok(SseTransport {
+            client,
+            endpoint,
+            headers,
+            reader,
+            closed,
+            mcp_name: mcp.name.clone(),
+        })
+    }
+
+    /// POST a message; any reply arrives on the event stream
+    pub async fn send(&self, message: &Value) -> Result<(), String> {
+        let body = message.to_string();
+        eprintln!("[SseTransport] >>> {}", body);
+
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .headers(self.headers.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("HTTP request to {} failed: {}", self.mcp_name, e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("HTTP error {} from {}: {}", status, self.mcp_name, text));
+        }
+        Ok(())
+    }
+
+    pub fn is_alive(&self) -> bool {
+        !self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Close the event stream, which ends the session
+    pub fn shutdown(&self) -> Result<(), String> {
+        self.reader.abort();
+        self.closed.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// Reads until the `endpoint` event, returning it along with any events that followed in the same chunk
+async fn read_endpoint(
+    response: &mut reqwest::Response,
+    parser: &mut SseParser,
+) -> Result<(String, Vec<SseEvent>), String> {
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read event stream: {}", e))?
+    {
+        let mut events = parser.feed(&chunk).into_iter();
+        for event in events.by_ref() {
+            if event.event.as_deref() == Some("endpoint") {
+                return Ok((event.data.trim().to_string(), events.collect()));
+            }
+        }
+    }
+
+    Err("Event stream closed before the endpoint event".to_string())
+}
+
+/// Background task forwarding stream messages until the server closes it
+async fn read_stream(
+    mut response: reqwest::Response,
+    mut parser: SseParser,
+    backlog: Vec<SseEvent>,
+    incoming: mpsc::UnboundedSender<Value>,
+    closed: Arc<AtomicBool>,
+    mcp_name: String,
+) {
+    let mut events = backlog;
+    loop {
+        for event in events.drain(..) {
+            forward_event(event, &incoming, &mcp_name);
+        }
+
+        match response.chunk().await {
+            Ok(Some(chunk)) => events = parser.feed(&chunk),
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("[SseTransport] Event stream error for {}: {}", mcp_name, e);
+                break;
+            }
+        }
+    }
+
+    eprintln!("[SseTransport] Event stream closed for: {}", mcp_name);
+    closed.store(true, Ordering::SeqCst);
+}
+
+fn forward_event(event: SseEvent, incoming: &mpsc::UnboundedSender<Value>, mcp_name: &str) {
+    if event.event.as_deref().is_some_and(|e| e != "message") {
+        return;
+    }
+
+    eprintln!("[SseTransport] <<< {}", event.data);
+
+    match serde_json::from_str(&event.data) {
+        Ok(message) => {
+            let _ = incoming.send(message);
+        }
+        Err(e) => eprintln!("[SseTransport] Skipping invalid event data from {}: {}", mcp_name, e),
+    }
+}
@@ -0,0 +1,193 @@
+use crate::models::*;
+use serde_json::Value;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::mpsc;
+
+/// Line-delimited JSON-RPC over the stdin/stdout of a Docker or Binary MCP process
+pub struct StdioTransport {
+    process: Mutex<Child>,
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    /// Set once the process closed its stdout
+    closed: Arc<AtomicBool>,
+    mcp_name: String,
+}
+
+impl StdioTransport {
+    /// Starts the MCP process; every message it writes to stdout is sent to `incoming`
+    pub fn spawn(
+        mcp: &Mcp,
+        env_vars: &[EnvVar],
+        incoming: mpsc::UnboundedSender<Value>,
+    ) -> Result<Self, String> {
+        let mut cmd = match &mcp.mcp_type {
+            McpType::Docker => {
+                let image = mcp
+                    .config
+                    .docker_image
+                    .as_ref()
+                    .ok_or("No docker image specified")?;
+
+                let mut cmd = Command::new("docker");
+                cmd.arg("run")
+                    .arg("--rm")
+                    .arg("-i") // Interactive mode for stdin
+                    .arg("--init"); // Use init process
+
+                // Add environment variables
+                for env_var in env_vars {
+                    cmd.arg("-e").arg(format!("{}={}", env_var.key, env_var.value));
+                }
+
+                cmd.arg(image);
+                cmd
+            }
+            McpType::Binary => {
+                let binary_path = mcp
+                    .config
+                    .binary_path
+                    .as_ref()
+                    .ok_or("No binary path specified")?;
+
+                let mut cmd = Command::new(binary_path);
+
+                // Add environment variables
+                for env_var in env_vars {
+                    cmd.env(&env_var.key, &env_var.value);
+                }
+
+                // Add arguments if specified
+                if !mcp.config.args.is_empty() {
+                    cmd.args(&mcp.config.args);
+                }
+
+                cmd
+            }
+            McpType::Http => {
+                return Err("HTTP MCPs not supported via stdio transport".to_string());
+            }
+        };
+
+        // Configure stdio
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        // Start the process
+        let mut process = cmd.spawn().map_err(|e| {
+            format!("Failed to start MCP process '{}': {}", mcp.name, e)
+        })?;
+
+        // Take ownership of stdin/stdout/stderr
+        let stdin = process
+            .stdin
+            .take()
+            .ok_or("Failed to open stdin for MCP process")?;
+        let stdout = process
+            .stdout
+            .take()
+            .ok_or("Failed to open stdout for MCP process")?;
+        let stderr = process
+            .stderr
+            .take()
+            .ok_or("Failed to open stderr for MCP process")?;
+
+        // Spawn task to read stderr
+        let mcp_name_clone = mcp.name.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                eprintln!("[McpClient stderr:{}] {}", mcp_name_clone, line);
+            }
+        });
+
+        let closed = Arc::new(AtomicBool::new(false));
+        tokio::spawn(read_stdout(stdout, incoming, closed.clone(), mcp.name.clone()));
+
+        eprintln!("[StdioTransport] Process started for: {}", mcp.name);
+
+        Ok(StdioTransport {
+            process: Mutex::new(process),
+            stdin: tokio::sync::Mutex::new(stdin),
+            closed,
+            mcp_name: mcp.name.clone(),
+        })
+    }
+
+    /// Write one message to the process stdin
+    pub async fn send(&self, message: &Value) -> Result<(), String> {
+        let line = message.to_string();
+        eprintln!("[StdioTransport] >>> {}", line);
+
+        let mut stdin = self.stdin.lock().await;
+        stdin
+            .write_all(format!("{}\n", line).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to stdin: {}", e))?;
+        stdin.flush().await.map_err(|e| format!("Failed to flush stdin: {}", e))
+    }
+
+    /// Check if the process is still running
+    pub fn is_alive(&self) -> bool {
+        if self.closed.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        let mut process = self.process.lock().unwrap();
+        match process.try_wait() {
+            Ok(Some(_)) => false, // Process has exited
+            Ok(None) => true,     // Process is still running
+            Err(_) => false,      // Error checking status
+        }
+    }
+
+    /// Kill the process
+    pub fn shutdown(&self) -> Result<(), String> {
+        let mut process = self.process.lock().unwrap();
+        process
+            .start_kill()
+            .map_err(|e| format!("Failed to kill process '{}': {}", self.mcp_name, e))
+    }
+}
+
+/// Background task forwarding every JSON line from the process until it closes stdout
+async fn read_stdout(
+    stdout: ChildStdout,
+    incoming: mpsc::UnboundedSender<Value>,
+    closed: Arc<AtomicBool>,
+    mcp_name: String,
+) {
+    let mut lines = BufReader::new(stdout).lines();
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) => {
+                let trimmed = line.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<Value>(trimmed) {
+                    Ok(message) => {
+                        eprintln!("[StdioTransport] <<< {}", trimmed);
+                        if incoming.send(message).is_err() {
+                            break;
+                        }
+                    }
+                    // Some servers log to stdout
+                    Err(_) => eprintln!("[StdioTransport] Skipping non-JSON line from {}: {}", mcp_name, trimmed),
+                }
+            }
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("[StdioTransport] Failed to read from {}: {}", mcp_name, e);
+                break;
+            }
+        }
+    }
+
+    eprintln!("[StdioTransport] Process closed stdout: {}", mcp_name);
+    closed.store(true, Ordering::SeqCst);
+}
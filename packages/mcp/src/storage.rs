@@ -1,16 +1,89 @@
 use crate::models::*;
+use chrono::Utc;
 use rusqlite::{params, Connection, Result};
-use std::path::PathBuf;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Router log entries older than this are deleted
+pub const ROUTER_LOG_RETENTION_DAYS: i64 = 30;
+
+/// Upper bound on stored router log entries; the oldest are deleted first
+pub const ROUTER_LOG_MAX_ROWS: i64 = 10_000;
 
 pub struct Storage {
-    conn: Connection,
+    conn: Mutex<Connection>,
 }
 
 impl Storage {
     pub fn new() -> Result<Self> {
-        let db_path = Self::get_db_path();
-        let conn = Connection::open(&db_path)?;
-        Ok(Storage { conn })
+        Self::open(&Self::get_db_path())
+    }
+
+    /// Opens the database at `db_path` instead of the desktop app's
+    pub fn open(db_path: &Path) -> Result<Self> {
+        let conn = Connection::open(db_path)?;
+        let storage = Storage {
+            conn: Mutex::new(conn),
+        };
+        storage.init_tables()?;
+        Ok(storage)
+    }
+
+    /// Creates the tables owned by the router; the rest are managed by the desktop app
+    fn init_tables(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tool_cache (
+                mcp_id TEXT NOT NULL,
+                config_hash TEXT NOT NULL,
+                tools TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY(mcp_id, config_hash)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS router_logs (
+                id TEXT PRIMARY KEY,
+                timestamp TEXT NOT NULL,
+                project_id TEXT NOT NULL,
+                mcp_id TEXT NOT NULL,
+                tool_name TEXT NOT NULL,
+                status TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                error TEXT,
+                args_size INTEGER,
+                result_size INTEGER
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_router_logs_timestamp ON router_logs(timestamp)",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tool_names (
+                scope TEXT NOT NULL,
+                exposed_name TEXT NOT NULL,
+                binding_id TEXT NOT NULL,
+                mcp_id TEXT NOT NULL,
+                tool_name TEXT NOT NULL,
+                collision TEXT,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY(scope, exposed_name)
+            )",
+            [],
+        )?;
+
+        // The app adds this too, but the router may run against an older database
+        add_column_if_missing(&conn, "project_mcp_bindings", "tool_settings", "TEXT NOT NULL DEFAULT '{}'")?;
+
+        Ok(())
     }
 
     fn get_db_path() -> PathBuf {
@@ -21,56 +94,91 @@ impl Storage {
         path
     }
 
-    /// Get all enabled MCPs with their bindings
-    pub fn get_enabled_mcps_with_bindings(&self) -> Result<Vec<(Mcp, ProjectMcpBinding)>, String> {
-        let mut stmt = self.conn
+    /// Get all registered projects
+    pub fn get_projects(&self) -> Result<Vec<Project>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT id, name, path, created_at FROM projects")
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let projects = stmt
+            .query_map([], |row| {
+                Ok(Project {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    path: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query projects: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect projects: {}", e))?;
+
+        Ok(projects)
+    }
+
+    /// Find a project by id, or by name when no id matches
+    pub fn find_project(&self, selector: &str) -> Result<Project, String> {
+        let projects = self.get_projects()?;
+
+        if let Some(project) = projects.iter().find(|p| p.id == selector) {
+            return Ok(project.clone());
+        }
+
+        let mut by_name = projects.into_iter().filter(|p| p.name == selector);
+        match (by_name.next(), by_name.next()) {
+            (Some(project), None) => Ok(project),
+            (Some(_), Some(_)) => Err(format!(
+                "Project name '{}' is ambiguous, use the project id instead",
+                selector
+            )),
+            (None, _) => Err(format!("Project not found: {}", selector)),
+        }
+    }
+
+    /// Get enabled MCPs with their bindings, limited to one project when given
+    pub fn get_enabled_mcps_with_bindings(
+        &self,
+        project_id: Option<&str>,
+    ) -> Result<Vec<(Mcp, ProjectMcpBinding)>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
             .prepare(
                 "SELECT
                     m.id, m.name, m.mcp_type, m.config, m.created_at,
-                    b.id, b.project_id, b.mcp_id, b.enabled, b.overrides
+                    b.id, b.project_id, b.mcp_id, b.enabled, b.overrides, b.tool_settings
                 FROM mcps m
                 INNER JOIN project_mcp_bindings b ON m.id = b.mcp_id
-                WHERE b.enabled = 1"
+                WHERE b.enabled = 1 AND (?1 IS NULL OR b.project_id = ?1)"
             )
             .map_err(|e| format!("Failed to prepare query: {}", e))?;
 
         let results = stmt
-            .query_map([], |row| {
-                // Parse MCP
-                let mcp_type_str: String = row.get(2)?;
-                let mcp_type = match mcp_type_str.as_str() {
-                    "docker" => McpType::Docker,
-                    "binary" => McpType::Binary,
-                    "http" => McpType::Http,
-                    _ => McpType::Binary,
-                };
-                let config_json: String = row.get(3)?;
-                let config: McpConfig = serde_json::from_str(&config_json)
-                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-
-                let mcp = Mcp {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    mcp_type,
-                    config,
-                    created_at: row.get(4)?,
-                };
-
-                // Parse Binding
-                let overrides_json: String = row.get(9)?;
-                let overrides: Vec<EnvVar> = serde_json::from_str(&overrides_json)
-                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-
-                let binding = ProjectMcpBinding {
-                    id: row.get(5)?,
-                    project_id: row.get(6)?,
-                    mcp_id: row.get(7)?,
-                    enabled: row.get::<_, i32>(8)? != 0,
-                    overrides,
-                };
-
-                Ok((mcp, binding))
-            })
+            .query_map(params![project_id], mcp_binding_from_row)
+            .map_err(|e| format!("Failed to query mcps: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect mcps: {}", e))?;
+
+        Ok(results)
+    }
+
+    /// Get every MCP bound to a project, enabled or not, in the order they were bound
+    pub fn get_project_mcps_with_bindings(&self, project_id: &str) -> Result<Vec<(Mcp, ProjectMcpBinding)>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT
+                    m.id, m.name, m.mcp_type, m.config, m.created_at,
+                    b.id, b.project_id, b.mcp_id, b.enabled, b.overrides, b.tool_settings
+                FROM mcps m
+                INNER JOIN project_mcp_bindings b ON m.id = b.mcp_id
+                WHERE b.project_id = ?1
+                ORDER BY b.rowid"
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let results = stmt
+            .query_map(params![project_id], mcp_binding_from_row)
             .map_err(|e| format!("Failed to query mcps: {}", e))?
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| format!("Failed to collect mcps: {}", e))?;
@@ -78,9 +186,55 @@ impl Storage {
         Ok(results)
     }
 
+    /// Enable or disable a binding, as the desktop app's toggle does
+    pub fn set_binding_enabled(&self, binding_id: &str, enabled: bool) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE project_mcp_bindings SET enabled = ?1 WHERE id = ?2",
+            params![enabled as i32, binding_id],
+        )
+        .map_err(|e| format!("Failed to update binding: {}", e))?;
+
+        Ok(())
+    }
+
+    /// The most recent routed call to an MCP from a project
+    pub fn last_router_log(&self, project_id: &str, mcp_id: &str) -> Result<Option<RouterLog>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, timestamp, project_id, mcp_id, tool_name, status, duration_ms, error, args_size, result_size
+                 FROM router_logs WHERE project_id = ?1 AND mcp_id = ?2
+                 ORDER BY timestamp DESC LIMIT 1",
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let mut rows = stmt
+            .query_map(params![project_id, mcp_id], |row| {
+                Ok(RouterLog {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    project_id: row.get(2)?,
+                    mcp_id: row.get(3)?,
+                    tool_name: row.get(4)?,
+                    status: row.get(5)?,
+                    duration_ms: row.get(6)?,
+                    error: row.get(7)?,
+                    args_size: row.get(8)?,
+                    result_size: row.get(9)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query router logs: {}", e))?;
+
+        rows.next()
+            .transpose()
+            .map_err(|e| format!("Failed to get router log: {}", e))
+    }
+
     /// Get encrypted secret by key
     pub fn get_encrypted_secret(&self, key: &str) -> Result<Option<String>, String> {
-        let mut stmt = self.conn
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
             .prepare("SELECT encrypted_value FROM secrets WHERE key = ?1")
             .map_err(|e| format!("Failed to prepare query: {}", e))?;
 
@@ -95,4 +249,213 @@ impl Storage {
             Ok(None)
         }
     }
+
+    /// Get the cached tool definitions for an MCP configuration
+    pub fn get_cached_tools(&self, mcp_id: &str, config_hash: &str) -> Result<Option<Vec<Value>>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT tools FROM tool_cache WHERE mcp_id = ?1 AND config_hash = ?2")
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let mut rows = stmt
+            .query(params![mcp_id, config_hash])
+            .map_err(|e| format!("Failed to query tool cache: {}", e))?;
+
+        if let Some(row) = rows.next().map_err(|e| format!("Failed to get row: {}", e))? {
+            let tools_json: String = row.get(0).map_err(|e| format!("Failed to get value: {}", e))?;
+            let tools = serde_json::from_str(&tools_json)
+                .map_err(|e| format!("Failed to parse cached tools: {}", e))?;
+            Ok(Some(tools))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Store the tool definitions discovered for an MCP configuration
+    pub fn save_cached_tools(&self, mcp_id: &str, config_hash: &str, tools: &[Value]) -> Result<(), String> {
+        let tools_json = serde_json::to_string(tools)
+            .map_err(|e| format!("Failed to serialize tools: {}", e))?;
+
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO tool_cache (mcp_id, config_hash, tools, updated_at) VALUES (?1, ?2, ?3, ?4)",
+            params![mcp_id, config_hash, tools_json, Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| format!("Failed to save tool cache: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Record a routed tool call
+    pub fn insert_router_log(&self, log: &RouterLog) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO router_logs (id, timestamp, project_id, mcp_id, tool_name, status, duration_ms, error, args_size, result_size)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                log.id,
+                log.timestamp,
+                log.project_id,
+                log.mcp_id,
+                log.tool_name,
+                log.status,
+                log.duration_ms,
+                log.error,
+                log.args_size,
+                log.result_size
+            ],
+        )
+        .map_err(|e| format!("Failed to save router log: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Apply the retention policy: drop entries older than
+    /// ROUTER_LOG_RETENTION_DAYS, then all but the newest ROUTER_LOG_MAX_ROWS
+    pub fn prune_router_logs(&self) -> Result<usize, String> {
+        let cutoff = (Utc::now() - chrono::Duration::days(ROUTER_LOG_RETENTION_DAYS)).to_rfc3339();
+
+        let conn = self.conn.lock().unwrap();
+        let expired = conn
+            .execute("DELETE FROM router_logs WHERE timestamp < ?1", params![cutoff])
+            .map_err(|e| format!("Failed to prune router logs: {}", e))?;
+        let overflow = conn
+            .execute(
+                "DELETE FROM router_logs WHERE id NOT IN (
+                    SELECT id FROM router_logs ORDER BY timestamp DESC LIMIT ?1
+                )",
+                params![ROUTER_LOG_MAX_ROWS],
+            )
+            .map_err(|e| format!("Failed to prune router logs: {}", e))?;
+
+        Ok(expired + overflow)
+    }
+
+    /// Replace the names of the tools listed for `binding_ids`; names of
+    /// bindings that failed to list are kept, those of removed bindings dropped
+    pub fn save_tool_names(&self, scope: &str, binding_ids: &[String], names: &[ToolName]) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        for binding_id in binding_ids {
+            tx.execute(
+                "DELETE FROM tool_names WHERE scope = ?1 AND binding_id = ?2",
+                params![scope, binding_id],
+            )
+            .map_err(|e| format!("Failed to clear tool names: {}", e))?;
+        }
+        tx.execute(
+            "DELETE FROM tool_names WHERE scope = ?1 AND binding_id NOT IN (
+                SELECT id FROM project_mcp_bindings WHERE enabled = 1
+            )",
+            params![scope],
+        )
+        .map_err(|e| format!("Failed to clear tool names: {}", e))?;
+
+        for name in names {
+            tx.execute(
+                "INSERT OR REPLACE INTO tool_names (scope, exposed_name, binding_id, mcp_id, tool_name, collision, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    name.scope,
+                    name.exposed_name,
+                    name.binding_id,
+                    name.mcp_id,
+                    name.tool_name,
+                    name.collision,
+                    name.updated_at
+                ],
+            )
+            .map_err(|e| format!("Failed to save tool name: {}", e))?;
+        }
+
+        tx.commit().map_err(|e| format!("Failed to save tool names: {}", e))
+    }
+
+    /// Look up which tool an exposed name was given to
+    pub fn find_tool_name(&self, scope: &str, exposed_name: &str) -> Result<Option<ToolName>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT scope, exposed_name, binding_id, mcp_id, tool_name, collision, updated_at
+                 FROM tool_names WHERE scope = ?1 AND exposed_name = ?2",
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let mut rows = stmt
+            .query_map(params![scope, exposed_name], |row| {
+                Ok(ToolName {
+                    scope: row.get(0)?,
+                    exposed_name: row.get(1)?,
+                    binding_id: row.get(2)?,
+                    mcp_id: row.get(3)?,
+                    tool_name: row.get(4)?,
+                    collision: row.get(5)?,
+                    updated_at: row.get(6)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query tool names: {}", e))?;
+
+        rows.next()
+            .transpose()
+            .map_err(|e| format!("Failed to get tool name: {}", e))
+    }
+}
+
+/// Parses the columns selected by the mcps-with-bindings queries
+fn mcp_binding_from_row(row: &rusqlite::Row) -> Result<(Mcp, ProjectMcpBinding)> {
+    // Parse MCP
+    let mcp_type_str: String = row.get(2)?;
+    let mcp_type = match mcp_type_str.as_str() {
+        "docker" => McpType::Docker,
+        "binary" => McpType::Binary,
+        "http" => McpType::Http,
+        _ => McpType::Binary,
+    };
+    let config_json: String = row.get(3)?;
+    let config: McpConfig = serde_json::from_str(&config_json)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+    let mcp = Mcp {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        mcp_type,
+        config,
+        created_at: row.get(4)?,
+    };
+
+    // Parse Binding
+    let overrides_json: String = row.get(9)?;
+    let overrides: Vec<EnvVar> = serde_json::from_str(&overrides_json)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    let tool_settings_json: String = row.get(10)?;
+    let tool_settings: ToolSettings = serde_json::from_str(&tool_settings_json)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+    let binding = ProjectMcpBinding {
+        id: row.get(5)?,
+        project_id: row.get(6)?,
+        mcp_id: row.get(7)?,
+        enabled: row.get::<_, i32>(8)? != 0,
+        overrides,
+        tool_settings,
+    };
+
+    Ok((mcp, binding))
+}
+
+/// Adds a column to a table created by an older version; tables that don't
+/// exist yet are left alone
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>>>()?;
+
+    if !columns.is_empty() && !columns.iter().any(|c| c == column) {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
 }
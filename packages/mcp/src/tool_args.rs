@@ -0,0 +1,172 @@
+use crate::models::ToolSettings;
+use jsonschema::Validator;
+use serde_json::Value;
+
+/// Rewrites a tool's input schema for the client: pinned arguments are
+/// removed, and arguments with a default become optional and show it
+pub fn advertised_schema(settings: &ToolSettings, schema: &mut Value) {
+    if settings.pinned_args.is_empty() && settings.default_args.is_empty() {
+        return;
+    }
+
+    if let Some(properties) = schema.get_mut("properties").and_then(|p| p.as_object_mut()) {
+        for name in settings.pinned_args.keys() {
+            properties.remove(name);
+        }
+        for (name, value) in &settings.default_args {
+            if let Some(property) = properties.get_mut(name).and_then(|p| p.as_object_mut()) {
+                property.insert("default".to_string(), value.clone());
+            }
+        }
+    }
+
+    if let Some(required) = schema.get_mut("required").and_then(|r| r.as_array_mut()) {
+        required.retain(|name| {
+            name.as_str().is_none_or(|name| {
+                !settings.pinned_args.contains_key(name) && !settings.default_args.contains_key(name)
+            })
+        });
+    }
+}
+
+/// Injects pinned arguments, replacing whatever the client sent, and fills in
+/// defaults for missing ones. Only arguments the tool declares are touched,
+/// unless its schema isn't known yet.
+pub fn apply(settings: &ToolSettings, schema: Option<&Value>, args: &mut Value) {
+    let Some(args) = args.as_object_mut() else {
+        return;
+    };
+
+    let declares = |name: &str| {
+        schema.is_none_or(|s| s.get("properties").and_then(|p| p.get(name)).is_some())
+    };
+
+    for (name, value) in &settings.pinned_args {
+        if declares(name) {
+            args.insert(name.clone(), value.clone());
+        }
+    }
+    for (name, value) in &settings.default_args {
+        if declares(name) && !args.contains_key(name) {
+            args.insert(name.clone(), value.clone());
+        }
+    }
+}
+
+/// Compiles a tool's input schema (JSON Schema, draft 2020-12 unless it
+/// declares another `$schema`). A schema that can't be compiled is logged and
+/// yields None; the server will report bad arguments itself.
+pub fn validator(schema: &Value) -> Option<Validator> {
+    match jsonschema::validator_for(schema) {
+        Ok(validator) => Some(validator),
+        Err(e) => {
+            eprintln!("[tool_args] Skipping validation, invalid inputSchema: {}", e);
+            None
+        }
+    }
+}
+
+/// Checks arguments against a compiled input schema, returning each violation
+pub fn validate(validator: &Validator, args: &Value) -> Result<(), Vec<String>> {
+    let violations: Vec<String> = validator
+        .iter_errors(args)
+        .map(|error| match error.instance_path().as_str() {
+            "" => error.to_string(),
+            path => format!("{}: {}", path, error),
+        })
+        .collect();
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn settings() -> ToolSettings {
+        let mut settings = ToolSettings::default();
+        settings.pinned_args.insert("repo".to_string(), json!("our-org/api"));
+        settings.default_args.insert("limit".to_string(), json!(10));
+        settings
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "repo": {"type": "string"},
+                "query": {"type": "string"},
+                "limit": {"type": "integer"}
+            },
+            "required": ["repo", "query", "limit"]
+        })
+    }
+
+    #[test]
+    fn test_advertised_schema() {
+        let mut schema = schema();
+        advertised_schema(&settings(), &mut schema);
+
+        assert_eq!(
+            schema,
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": {"type": "string"},
+                    "limit": {"type": "integer", "default": 10}
+                },
+                "required": ["query"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut args = json!({"repo": "someone/else", "query": "bug"});
+        apply(&settings(), Some(&schema()), &mut args);
+        assert_eq!(args, json!({"repo": "our-org/api", "query": "bug", "limit": 10}));
+
+        let mut args = json!({"query": "bug", "limit": 3});
+        apply(&settings(), Some(&schema()), &mut args);
+        assert_eq!(args, json!({"repo": "our-org/api", "query": "bug", "limit": 3}));
+
+        // Tools that don't take the argument are left alone
+        let mut args = json!({"path": "/tmp"});
+        apply(&settings(), Some(&json!({"properties": {"path": {}}})), &mut args);
+        assert_eq!(args, json!({"path": "/tmp"}));
+
+        let mut args = json!({});
+        apply(&settings(), None, &mut args);
+        assert_eq!(args, json!({"repo": "our-org/api", "limit": 10}));
+    }
+
+    fn check(schema: &Value, args: &Value) -> Result<(), Vec<String>> {
+        match validator(schema) {
+            Some(validator) => validate(&validator, args),
+            None => Ok(()),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(check(&schema(), &json!({"repo": "a/b", "query": "x", "limit": 1})).is_ok());
+
+        let violations = check(&schema(), &json!({"repo": "a/b", "limit": "ten"})).unwrap_err();
+        assert_eq!(violations.len(), 2);
+        assert!(violations.iter().any(|v| v.contains("\"query\" is a required property")));
+        assert!(violations.iter().any(|v| v.starts_with("/limit: ")));
+
+        // 2020-12 keywords apply without a `$schema`
+        let tuple = json!({"properties": {"pair": {"prefixItems": [{"type": "string"}], "items": false}}});
+        assert!(check(&tuple, &json!({"pair": ["a"]})).is_ok());
+        assert!(check(&tuple, &json!({"pair": ["a", "b"]})).is_err());
+
+        // Broken schemas don't block the call
+        assert!(check(&json!({"type": 5}), &json!({})).is_ok());
+    }
+}
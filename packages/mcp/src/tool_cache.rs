@@ -0,0 +1,185 @@
+use crate::models::*;
+use crate::storage::Storage;
+use crate::tool_args;
+use jsonschema::Validator;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Cached tool lists older than this are refreshed in the background
+const CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Identifies a cached tool list: the server and the configuration it was launched with
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub mcp_id: String,
+    pub config_hash: String,
+}
+
+impl CacheKey {
+    pub fn new(mcp: &Mcp, binding: &ProjectMcpBinding) -> Self {
+        // Secrets are referenced by key in both config and overrides, so no
+        // secret value ends up in the hash
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{:?}", mcp.mcp_type));
+        hasher.update(serde_json::to_string(&mcp.config).unwrap_or_default());
+        hasher.update(serde_json::to_string(&binding.overrides).unwrap_or_default());
+
+        let config_hash = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        CacheKey {
+            mcp_id: mcp.id.clone(),
+            config_hash,
+        }
+    }
+}
+
+struct CacheEntry {
+    tools: Vec<Value>,
+    /// None for entries loaded from SQLite that haven't been confirmed by this process
+    refreshed_at: Option<Instant>,
+}
+
+/// A tool's compiled inputSchema, with the schema it was compiled from
+struct CachedValidator {
+    schema: Value,
+    /// None if the schema doesn't compile
+    validator: Option<Arc<Validator>>,
+}
+
+/// Tool definitions discovered from downstream servers, kept in memory and in SQLite
+pub struct ToolCache {
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+    refreshing: Mutex<HashSet<CacheKey>>,
+    /// By cache key and tool name; a key's validators are dropped when its tools change
+    validators: Mutex<HashMap<(CacheKey, String), CachedValidator>>,
+}
+
+impl ToolCache {
+    pub fn new() -> Self {
+        ToolCache {
+            entries: Mutex::new(HashMap::new()),
+            refreshing: Mutex::new(HashSet::new()),
+            validators: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cached tools for the key, loaded from SQLite on first access.
+    /// The flag tells whether the entry is due for a refresh.
+    pub fn get(&self, storage: &Storage, key: &CacheKey) -> Option<(Vec<Value>, bool)> {
+        let mut entries = self.entries.lock().unwrap();
+
+        if !entries.contains_key(key) {
+            match storage.get_cached_tools(&key.mcp_id, &key.config_hash) {
+                Ok(Some(tools)) => {
+                    entries.insert(
+                        key.clone(),
+                        CacheEntry {
+                            tools,
+                            refreshed_at: None,
+                        },
+                    );
+                }
+                Ok(None) => return None,
+                Err(e) => {
+                    eprintln!("[ToolCache] Failed to load cached tools: {}", e);
+                    return None;
+                }
+            }
+        }
+
+        entries.get(key).map(|entry| {
+            let stale = entry
+                .refreshed_at
+                .is_none_or(|at| at.elapsed() >= CACHE_TTL);
+            (entry.tools.clone(), stale)
+        })
+    }
+
+    /// Stores freshly discovered tools. Returns true if they differ from the cached set.
+    pub fn update(&self, storage: &Storage, key: &CacheKey, tools: Vec<Value>) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+
+        let changed = entries.get(key).is_none_or(|entry| entry.tools != tools);
+        if changed {
+            if let Err(e) = storage.save_cached_tools(&key.mcp_id, &key.config_hash, &tools) {
+                eprintln!("[ToolCache] {}", e);
+            }
+            self.validators.lock().unwrap().retain(|(k, _), _| k != key);
+        }
+
+        entries.insert(
+            key.clone(),
+            CacheEntry {
+                tools,
+                refreshed_at: Some(Instant::now()),
+            },
+        );
+
+        changed
+    }
+
+    /// The compiled validator for a tool's input schema, compiled on first use
+    /// and again whenever the schema differs from the one it was compiled from
+    pub fn validator(&self, key: &CacheKey, tool_name: &str, schema: &Value) -> Option<Arc<Validator>> {
+        let validator_key = (key.clone(), tool_name.to_string());
+        if let Some(cached) = self.validators.lock().unwrap().get(&validator_key) {
+            if cached.schema == *schema {
+                return cached.validator.clone();
+            }
+        }
+
+        // Compiled without the lock held; a concurrent call may compile it too
+        let validator = tool_args::validator(schema).map(Arc::new);
+        self.validators.lock().unwrap().insert(
+            validator_key,
+            CachedValidator {
+                schema: schema.clone(),
+                validator: validator.clone(),
+            },
+        );
+        validator
+    }
+
+    /// Marks a key as being refreshed. Returns false if a refresh is already running.
+    pub fn begin_refresh(&self, key: &CacheKey) -> bool {
+        self.refreshing.lock().unwrap().insert(key.clone())
+    }
+
+    pub fn end_refresh(&self, key: &CacheKey) {
+        self.refreshing.lock().unwrap().remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validators_are_reused_until_the_schema_changes() {
+        let cache = ToolCache::new();
+        let key = CacheKey {
+            mcp_id: "m1".to_string(),
+            config_hash: "h".to_string(),
+        };
+        let schema = json!({"type": "object", "required": ["q"]});
+
+        let first = cache.validator(&key, "search", &schema).unwrap();
+        let again = cache.validator(&key, "search", &schema).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        let changed = json!({"type": "object"});
+        let recompiled = cache.validator(&key, "search", &changed).unwrap();
+        assert!(!Arc::ptr_eq(&first, &recompiled));
+        assert!(recompiled.is_valid(&json!({})));
+
+        assert!(cache.validator(&key, "broken", &json!({"type": 5})).is_none());
+    }
+}
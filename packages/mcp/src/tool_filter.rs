@@ -0,0 +1,86 @@
+use crate::models::ToolSettings;
+
+/// Whether a binding exposes a downstream tool: it must match one of the
+/// allowed patterns, if any are set, and none of the denied ones
+pub fn is_tool_allowed(settings: &ToolSettings, tool_name: &str) -> bool {
+    let allowed = settings.allowed_tools.is_empty()
+        || settings.allowed_tools.iter().any(|p| glob_match(p, tool_name));
+    allowed && !settings.denied_tools.iter().any(|p| glob_match(p, tool_name))
+}
+
+/// Matches `name` against a pattern where `*` stands for any run of
+/// characters and `?` for exactly one
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star, at)) => {
+                    p = star + 1;
+                    n = at + 1;
+                    backtrack = Some((star, at + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(allowed: &[&str], denied: &[&str]) -> ToolSettings {
+        ToolSettings {
+            allowed_tools: allowed.iter().map(|s| s.to_string()).collect(),
+            denied_tools: denied.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("read_file", "read_file"));
+        assert!(!glob_match("read_file", "read_files"));
+        assert!(glob_match("read_*", "read_file"));
+        assert!(glob_match("*_file", "write_file"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("get_?", "get_a"));
+        assert!(!glob_match("get_?", "get_ab"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+    }
+
+    #[test]
+    fn test_is_tool_allowed() {
+        assert!(is_tool_allowed(&settings(&[], &[]), "anything"));
+
+        let read_only = settings(&["read_*", "list_*"], &[]);
+        assert!(is_tool_allowed(&read_only, "read_file"));
+        assert!(!is_tool_allowed(&read_only, "write_file"));
+
+        // Deny wins over allow
+        let no_secrets = settings(&["read_*"], &["read_secret*"]);
+        assert!(is_tool_allowed(&no_secrets, "read_file"));
+        assert!(!is_tool_allowed(&no_secrets, "read_secrets"));
+
+        assert!(!is_tool_allowed(&settings(&[], &["delete_*"]), "delete_repo"));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::mcp_protocol::McpTool;
+use crate::models::*;
+
+/// Name a tool is exposed under (before the server prefix): its alias if the
+/// binding renames it, otherwise the server's name
+pub fn exposed_name<'a>(settings: &'a ToolSettings, tool_name: &'a str) -> &'a str {
+    settings
+        .overrides
+        .get(tool_name)
+        .and_then(alias)
+        .unwrap_or(tool_name)
+}
+
+/// Maps an exposed name back to the server's tool name. A renamed tool is
+/// only reachable through its alias, so the old name can't shadow another alias.
+pub fn original_name<'a>(settings: &'a ToolSettings, exposed: &'a str) -> Option<&'a str> {
+    if let Some((tool_name, _)) = settings
+        .overrides
+        .iter()
+        .find(|(_, o)| alias(o) == Some(exposed))
+    {
+        return Some(tool_name);
+    }
+
+    match settings.overrides.get(exposed).and_then(alias) {
+        Some(_) => None,
+        None => Some(exposed),
+    }
+}
+
+/// Applies the binding's description and title overrides to a listed tool
+pub fn apply(settings: &ToolSettings, tool_name: &str, tool: &mut McpTool) {
+    let Some(tool_override) = settings.overrides.get(tool_name) else {
+        return;
+    };
+
+    if let Some(description) = &tool_override.description {
+        tool.description = Some(description.clone());
+    }
+    if let Some(suffix) = tool_override.description_suffix.as_deref().filter(|s| !s.is_empty()) {
+        tool.description = Some(match tool.description.as_deref().filter(|d| !d.is_empty()) {
+            Some(description) => format!("{}\n\n{}", description, suffix),
+            None => suffix.to_string(),
+        });
+    }
+    if let Some(title) = &tool_override.title {
+        tool.title = Some(title.clone());
+    }
+}
+
+fn alias(tool_override: &ToolOverride) -> Option<&str> {
+    tool_override.name.as_deref().filter(|n| !n.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn renamed(pairs: &[(&str, &str)]) -> ToolSettings {
+        let mut settings = ToolSettings::default();
+        for (tool_name, alias) in pairs {
+            settings.overrides.insert(
+                tool_name.to_string(),
+                ToolOverride {
+                    name: Some(alias.to_string()),
+                    ..Default::default()
+                },
+            );
+        }
+        settings
+    }
+
+    #[test]
+    fn test_rename_round_trip() {
+        let settings = renamed(&[("search", "search_issues"), ("query", "search")]);
+
+        assert_eq!(exposed_name(&settings, "search"), "search_issues");
+        assert_eq!(exposed_name(&settings, "query"), "search");
+        assert_eq!(exposed_name(&settings, "list"), "list");
+
+        assert_eq!(original_name(&settings, "search_issues"), Some("search"));
+        assert_eq!(original_name(&settings, "search"), Some("query"));
+        assert_eq!(original_name(&settings, "list"), Some("list"));
+        // Renamed away
+        assert_eq!(original_name(&settings, "query"), None);
+    }
+
+    #[test]
+    fn test_apply_description_and_title() {
+        let mut settings = ToolSettings::default();
+        settings.overrides.insert(
+            "echo".to_string(),
+            ToolOverride {
+                description_suffix: Some("Only use for tests.".to_string()),
+                title: Some("Echo".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let mut tool = McpTool {
+            name: "fake__echo".to_string(),
+            title: None,
+            description: Some("Echo args".to_string()),
+            input_schema: json!({}),
+            output_schema: None,
+            annotations: None,
+            meta: None,
+            extra: Default::default(),
+        };
+        apply(&settings, "echo", &mut tool);
+        assert_eq!(tool.description.as_deref(), Some("Echo args\n\nOnly use for tests."));
+        assert_eq!(tool.title.as_deref(), Some("Echo"));
+
+        settings.overrides.get_mut("echo").unwrap().description = Some("Repeat".to_string());
+        apply(&settings, "echo", &mut tool);
+        assert_eq!(tool.description.as_deref(), Some("Repeat\n\nOnly use for tests."));
+    }
+}
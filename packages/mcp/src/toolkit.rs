@@ -0,0 +1,253 @@
+use crate::mcp_protocol::{CallToolResult, McpTool, ToolContent};
+use crate::models::*;
+use crate::naming;
+use crate::peer::ClientPeer;
+use crate::router::Router;
+use crate::tool_cache::CacheKey;
+use serde_json::{json, Value};
+
+/// Prefix of the router's own tools, which let agents manage the active
+/// project's servers
+pub const PREFIX: &str = "toolkit";
+
+const LIST_SERVERS: &str = "list_servers";
+const SERVER_STATUS: &str = "server_status";
+const ENABLE_SERVER: &str = "enable_server";
+const DISABLE_SERVER: &str = "disable_server";
+
+const BUILTIN_TOOLS: &[&str] = &[LIST_SERVERS, SERVER_STATUS, ENABLE_SERVER, DISABLE_SERVER];
+
+/// The built-in tool an exposed name refers to, if any
+pub fn builtin_name(exposed_name: &str) -> Option<&str> {
+    let name = exposed_name
+        .strip_prefix(PREFIX)?
+        .strip_prefix(naming::DEFAULT_SEPARATOR)?;
+    BUILTIN_TOOLS.contains(&name).then_some(name)
+}
+
+/// Definitions of the built-in tools, named like a bound server's
+pub fn tools() -> Vec<McpTool> {
+    let server_arg = |description: &str| json!({ "type": "string", "description": description });
+    let read_only = json!({ "readOnlyHint": true });
+    let toggle = json!({ "readOnlyHint": false, "destructiveHint": false, "idempotentHint": true });
+
+    vec![
+        tool(
+            LIST_SERVERS,
+            "List servers",
+            "List the MCP servers bound to the current project and whether each is enabled.",
+            json!({ "type": "object", "properties": {} }),
+            read_only.clone(),
+        ),
+        tool(
+            SERVER_STATUS,
+            "Server status",
+            "Show whether the project's servers are running, how many tools they expose and how their last call went.",
+            json!({
+                "type": "object",
+                "properties": { "server": server_arg("Server name or prefix; all servers when omitted") }
+            }),
+            read_only,
+        ),
+        tool(
+            ENABLE_SERVER,
+            "Enable server",
+            "Enable a server bound to the current project so its tools, resources and prompts become available.",
+            json!({
+                "type": "object",
+                "properties": { "server": server_arg("Server name or prefix, as returned by list_servers") },
+                "required": ["server"]
+            }),
+            toggle.clone(),
+        ),
+        tool(
+            DISABLE_SERVER,
+            "Disable server",
+            "Disable a server bound to the current project, removing its tools and stopping its process.",
+            json!({
+                "type": "object",
+                "properties": { "server": server_arg("Server name or prefix, as returned by list_servers") },
+                "required": ["server"]
+            }),
+            toggle,
+        ),
+    ]
+}
+
+fn tool(name: &str, title: &str, description: &str, input_schema: Value, annotations: Value) -> McpTool {
+    McpTool {
+        name: format!("{}{}{}", PREFIX, naming::DEFAULT_SEPARATOR, name),
+        title: Some(title.to_string()),
+        description: Some(description.to_string()),
+        input_schema,
+        output_schema: None,
+        annotations: Some(annotations),
+        meta: None,
+        extra: Default::default(),
+    }
+}
+
+/// Runs a built-in tool against the active project's bindings. Failures are
+/// reported as tool errors so the agent can correct itself.
+pub fn call(router: &Router, peer: &ClientPeer, tool_name: &str, args: &Value) -> CallToolResult {
+    match run(router, peer, tool_name, args) {
+        Ok(output) => CallToolResult {
+            content: vec![ToolContent::Text {
+                text: serde_json::to_string_pretty(&output).unwrap_or_default(),
+                annotations: None,
+                meta: None,
+            }],
+            structured_content: Some(output),
+            is_error: None,
+            meta: None,
+        },
+        Err(e) => CallToolResult::error(format!("Error: {}", e)),
+    }
+}
+
+fn run(router: &Router, peer: &ClientPeer, tool_name: &str, args: &Value) -> Result<Value, String> {
+    let project = router
+        .project()
+        .ok_or("No project is selected, so there are no project servers to manage")?;
+    let bindings = router.storage.get_project_mcps_with_bindings(&project.id)?;
+    let server_arg = args.get("server").and_then(|s| s.as_str());
+
+    match tool_name {
+        LIST_SERVERS => Ok(json!({
+            "project": project.name,
+            "servers": bindings.iter().map(|(mcp, binding)| summary(mcp, binding)).collect::<Vec<_>>(),
+        })),
+        SERVER_STATUS => {
+            let selected = match server_arg {
+                Some(server) => vec![find_server(&bindings, server)?],
+                None => bindings.iter().collect(),
+            };
+            let servers = selected
+                .into_iter()
+                .map(|(mcp, binding)| status(router, mcp, binding))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(json!({ "project": project.name, "servers": servers }))
+        }
+        ENABLE_SERVER | DISABLE_SERVER => {
+            let enabled = tool_name == ENABLE_SERVER;
+            let server = server_arg.ok_or("Missing required argument: server")?;
+            let (mcp, binding) = find_server(&bindings, server)?;
+            let changed = binding.enabled != enabled;
+
+            if changed {
+                router.storage.set_binding_enabled(&binding.id, enabled)?;
+                eprintln!(
+                    "[Toolkit] {} {} for project {}",
+                    if enabled { "Enabled" } else { "Disabled" },
+                    mcp.name,
+                    project.name
+                );
+                // The client lists tools again, which also releases a disabled server's process
+                peer.notify("notifications/tools/list_changed", None);
+            }
+
+            Ok(json!({ "server": mcp.name, "enabled": enabled, "changed": changed }))
+        }
+        _ => Err(format!("Unknown toolkit tool: {}", tool_name)),
+    }
+}
+
+/// Finds a binding by server name, exposed prefix or binding id
+fn find_server<'a>(
+    bindings: &'a [(Mcp, ProjectMcpBinding)],
+    server: &str,
+) -> Result<&'a (Mcp, ProjectMcpBinding), String> {
+    bindings
+        .iter()
+        .find(|(mcp, binding)| mcp.name == server || binding.id == server)
+        .or_else(|| bindings.iter().find(|(mcp, binding)| naming::prefix(mcp, binding) == server))
+        .ok_or_else(|| {
+            format!(
+                "No server named '{}' is bound to this project; call {}{}{} to see them",
+                server,
+                PREFIX,
+                naming::DEFAULT_SEPARATOR,
+                LIST_SERVERS
+            )
+        })
+}
+
+fn summary(mcp: &Mcp, binding: &ProjectMcpBinding) -> Value {
+    json!({
+        "server": mcp.name,
+        "prefix": naming::prefix(mcp, binding),
+        "type": mcp.mcp_type,
+        "enabled": binding.enabled,
+    })
+}
+
+fn status(router: &Router, mcp: &Mcp, binding: &ProjectMcpBinding) -> Result<Value, String> {
+    let env_vars = router.resolve_env_vars(mcp, binding);
+    let tools = router
+        .tools
+        .get(&router.storage, &CacheKey::new(mcp, binding))
+        .map(|(tools, _)| tools.len());
+    let last_call = router
+        .storage
+        .last_router_log(&binding.project_id, &mcp.id)?
+        .map(|log| {
+            json!({
+                "tool": log.tool_name,
+                "status": log.status,
+                "timestamp": log.timestamp,
+                "duration_ms": log.duration_ms,
+                "error": log.error,
+            })
+        });
+
+    let mut status = summary(mcp, binding);
+    status["process"] = json!(router.pool.status(&router.session_id, mcp, binding, &env_vars));
+    status["tools"] = json!(tools);
+    status["last_call"] = json!(last_call);
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bound(id: &str, name: &str, prefix: Option<&str>) -> (Mcp, ProjectMcpBinding) {
+        let mcp = serde_json::from_value(json!({
+            "id": format!("m-{}", id),
+            "name": name,
+            "mcp_type": "Binary",
+            "config": { "docker_image": null, "binary_path": null, "http_url": null, "command": "srv", "args": [], "env_vars": [] },
+            "created_at": "",
+        }))
+        .unwrap();
+        let binding = serde_json::from_value(json!({
+            "id": id,
+            "project_id": "p1",
+            "mcp_id": format!("m-{}", id),
+            "enabled": true,
+            "overrides": [],
+            "tool_settings": { "prefix": prefix },
+        }))
+        .unwrap();
+        (mcp, binding)
+    }
+
+    #[test]
+    fn test_builtin_name() {
+        assert_eq!(builtin_name("toolkit__enable_server"), Some("enable_server"));
+        assert_eq!(builtin_name("toolkit__search"), None);
+        assert_eq!(builtin_name("github__list_servers"), None);
+        assert!(tools().iter().all(|t| builtin_name(&t.name).is_some()));
+    }
+
+    #[test]
+    fn test_find_server() {
+        let bindings = vec![bound("b1", "my-db", None), bound("b2", "GitHub", Some("gh"))];
+
+        assert_eq!(find_server(&bindings, "my-db").unwrap().1.id, "b1");
+        assert_eq!(find_server(&bindings, "my_db").unwrap().1.id, "b1");
+        assert_eq!(find_server(&bindings, "gh").unwrap().1.id, "b2");
+        assert_eq!(find_server(&bindings, "b2").unwrap().1.id, "b2");
+        assert!(find_server(&bindings, "jira").is_err());
+    }
+}